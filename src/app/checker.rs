@@ -1,11 +1,44 @@
+use std::fmt::Display;
+use std::io;
+
+use serde::Serialize;
+
+use crate::app::codec;
+use crate::app::types::OutputFormat;
 use crate::cpu::Checker;
 use crate::sgx;
 
-pub fn check_sgx_availability(quite: bool) -> Result<(), String> {
+/// Structured counterpart of the text dumped by `Checker`, which also reports the PSW status.
+#[derive(Serialize)]
+struct Availability<'a> {
+    #[serde(flatten)]
+    cpu: &'a Checker,
+    driver_loaded: bool,
+    aesmd_installed: bool,
+    psw_installed: bool,
+}
+
+impl Display for Availability<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.cpu)
+    }
+}
+
+pub fn check_sgx_availability(quite: bool, format: OutputFormat) -> Result<(), String> {
     let cc = Checker::new();
 
     if !quite {
-        println!("{}", cc);
+        if format == OutputFormat::Text {
+            println!("{}", cc);
+        } else {
+            let v = Availability {
+                cpu: &cc,
+                driver_loaded: sgx::driver_loaded(),
+                aesmd_installed: sgx::aesmd_installed(),
+                psw_installed: sgx::psw_installed(),
+            };
+            codec::encode(&mut io::stdout(), &v, format)?;
+        }
     }
 
     if !cc.cpuid_supported
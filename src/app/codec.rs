@@ -0,0 +1,435 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use openssl::base64;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::app::types::{InputEncoding, OutputFormat};
+
+/// Decodes the binary input according to `encoding`, detecting the encoding if it's
+/// `InputEncoding::Auto`.
+pub fn decode_input(b: &[u8], encoding: InputEncoding) -> Result<Vec<u8>, String> {
+    let encoding = match encoding {
+        InputEncoding::Auto => detect_input_encoding(b),
+        v => v,
+    };
+
+    let text = || -> Result<String, String> {
+        let s = std::str::from_utf8(b).map_err(|err| format!("decode UTF-8: {err}"))?;
+        Ok(s.chars().filter(|c| !c.is_ascii_whitespace()).collect())
+    };
+
+    match encoding {
+        InputEncoding::Auto | InputEncoding::Raw => Ok(b.to_vec()),
+        InputEncoding::Hex => {
+            let s = text()?;
+            let s = s
+                .strip_prefix("0x")
+                .or_else(|| s.strip_prefix("0X"))
+                .unwrap_or(&s);
+            decode_hex(s).map_err(|err| format!("decode hex: {err}"))
+        }
+        InputEncoding::Base64 => decode_base64(&text()?),
+        InputEncoding::Base64url => decode_base64(&text()?.replace('-', "+").replace('_', "/")),
+        InputEncoding::Pem => {
+            let s = std::str::from_utf8(b).map_err(|err| format!("decode UTF-8: {err}"))?;
+            let body: String = s
+                .lines()
+                .map(|v| v.trim())
+                .skip_while(|v| !v.starts_with("-----BEGIN"))
+                .skip(1)
+                .take_while(|v| !v.starts_with("-----END"))
+                .collect();
+            decode_base64(&body).map_err(|err| format!("decode PEM: {err}"))
+        }
+    }
+}
+
+/// Decodes a hex string, which the encoding crate has no decoder for.
+#[allow(clippy::manual_is_multiple_of)]
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    if s.len() % 2 != 0 {
+        return Err(format!("odd length: {}", s.len()));
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..(i + 2))
+                .and_then(|v| u8::from_str_radix(v, 16).ok())
+                .ok_or_else(|| format!("bad hex at {i}"))
+        })
+        .collect()
+}
+
+/// Writes `v` in the given format, ending with a line break.
+pub fn encode<W, T>(out: &mut W, v: &T, format: OutputFormat) -> Result<(), String>
+where
+    W: Write,
+    T: Display + Serialize,
+{
+    let encoded = match format {
+        OutputFormat::Text => v.to_string(),
+        _ => serialize(v, format)?,
+    };
+
+    writeln!(out, "{}", encoded.trim_end()).map_err(|err| format!("write: {err}"))
+}
+
+/// Writes `v` as one of the records dumped from multiple inputs if `file` is given, or the same as
+/// [`encode`] otherwise.
+///
+/// Text records are headed by the file name, YAML records are separate documents and TOML records
+/// are items of the `records` array of tables.
+pub fn encode_record<W, T>(
+    out: &mut W,
+    file: Option<&str>,
+    v: &T,
+    format: OutputFormat,
+) -> Result<(), String>
+where
+    W: Write,
+    T: Display + Serialize,
+{
+    let file = match file {
+        Some(v) => v,
+        None => return encode(out, v, format),
+    };
+
+    let record = Record { file, record: v };
+    let encoded = match format {
+        OutputFormat::Text => {
+            write_record_header(out, file)?;
+            v.to_string()
+        }
+        OutputFormat::Json => serialize(&record, format)?,
+        OutputFormat::Yaml => format!("---\n{}", serialize(&record, format)?),
+        OutputFormat::Toml => serialize(&Records { records: [record] }, format)?,
+    };
+
+    writeln!(out, "{}", encoded.trim_end()).map_err(|err| format!("write: {err}"))
+}
+
+/// Writes the raw values of `fields` selected from `v` one per line, headed by `file` if given.
+///
+/// Each field is a dotted path of names following the JSON encoding of `v`, such as
+/// `body.mr_enclave`, where array items are selected by indexes. Strings are written without
+/// quotes, and objects and arrays as compact JSON.
+pub fn encode_fields<W, T>(
+    out: &mut W,
+    file: Option<&str>,
+    v: &T,
+    fields: &[String],
+) -> Result<(), String>
+where
+    W: Write,
+    T: Serialize,
+{
+    let v = serde_json::to_value(v).map_err(|err| format!("encode JSON: {err}"))?;
+
+    let mut values = Vec::with_capacity(fields.len());
+    for field in fields {
+        let selected = select_field(&v, field)?;
+        let value = match selected {
+            serde_json::Value::String(s) => s.clone(),
+            v => v.to_string(),
+        };
+        values.push(value);
+    }
+
+    if let Some(file) = file {
+        write_record_header(out, file)?;
+    }
+    for v in values {
+        writeln!(out, "{v}").map_err(|err| format!("write: {err}"))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the header preceding a text record read from `file`.
+pub fn write_record_header<W>(out: &mut W, file: &str) -> Result<(), String>
+where
+    W: Write,
+{
+    writeln!(out, "==> {file} <==").map_err(|err| format!("write: {err}"))
+}
+
+#[derive(Serialize)]
+struct Record<'a, T> {
+    file: &'a str,
+    record: &'a T,
+}
+
+#[derive(Serialize)]
+struct Records<'a, T> {
+    records: [Record<'a, T>; 1],
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+fn decode_base64(s: &str) -> Result<Vec<u8>, String> {
+    let mut s = s.to_string();
+    while s.len() % 4 != 0 {
+        s.push('=');
+    }
+
+    base64::decode_block(&s).map_err(|err| format!("decode base64: {err}"))
+}
+
+/// Tells the encoding by the characters in use, where raw binary is assumed if any byte isn't a
+/// printable ASCII. Hex is preferred over base64 in case both fit.
+#[allow(clippy::manual_is_multiple_of)]
+fn detect_input_encoding(b: &[u8]) -> InputEncoding {
+    let is_text = b
+        .iter()
+        .all(|v| v.is_ascii_graphic() || v.is_ascii_whitespace());
+    if b.is_empty() || !is_text {
+        return InputEncoding::Raw;
+    }
+
+    let s: Vec<u8> = b
+        .iter()
+        .copied()
+        .filter(|v| !v.is_ascii_whitespace())
+        .collect();
+    if s.starts_with(b"-----BEGIN") {
+        return InputEncoding::Pem;
+    }
+
+    let hex = s
+        .strip_prefix(b"0x")
+        .or_else(|| s.strip_prefix(b"0X"))
+        .unwrap_or(&s);
+    if hex.len() % 2 == 0 && hex.iter().all(|v| v.is_ascii_hexdigit()) {
+        return InputEncoding::Hex;
+    }
+
+    let payload = match s.iter().position(|v| *v == b'=') {
+        Some(i) if s[i..].iter().all(|v| *v == b'=') => &s[..i],
+        Some(_) => return InputEncoding::Raw,
+        None => &s[..],
+    };
+    if payload
+        .iter()
+        .all(|v| v.is_ascii_alphanumeric() || matches!(v, b'+' | b'/'))
+    {
+        InputEncoding::Base64
+    } else if payload
+        .iter()
+        .all(|v| v.is_ascii_alphanumeric() || matches!(v, b'-' | b'_'))
+    {
+        InputEncoding::Base64url
+    } else {
+        InputEncoding::Raw
+    }
+}
+
+/// Serializes a value with plain values ahead of tables in every mapping as TOML requires, while
+/// keeping fields in their order otherwise. Null fields are skipped since TOML has no null.
+struct ValuesFirst<'a>(&'a serde_yaml::Value);
+
+impl Serialize for ValuesFirst<'_> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let is_table = |v: &serde_yaml::Value| match v {
+            serde_yaml::Value::Mapping(_) => true,
+            serde_yaml::Value::Sequence(v) => !v.is_empty() && v.iter().all(|v| v.is_mapping()),
+            _ => false,
+        };
+
+        match self.0 {
+            serde_yaml::Value::Mapping(v) => {
+                let mut m = s.serialize_map(None)?;
+                for tables in [false, true] {
+                    for (k, v) in v.iter().filter(|(_, v)| !v.is_null()) {
+                        if is_table(v) == tables {
+                            m.serialize_entry(k, &ValuesFirst(v))?;
+                        }
+                    }
+                }
+                m.end()
+            }
+            serde_yaml::Value::Sequence(v) => s.collect_seq(v.iter().map(ValuesFirst)),
+            v => v.serialize(s),
+        }
+    }
+}
+
+fn serialize<T>(v: &T, format: OutputFormat) -> Result<String, String>
+where
+    T: Serialize,
+{
+    match format {
+        OutputFormat::Text => Err("text is written by Display instead".to_string()),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(v).map_err(|err| format!("encode JSON: {err}"))
+        }
+        OutputFormat::Yaml => serde_yaml::to_string(v).map_err(|err| format!("encode YAML: {err}")),
+        // `serde_yaml::Value` keeps the order of fields, unlike `toml::Value` sorting them.
+        OutputFormat::Toml => serde_yaml::to_value(v)
+            .map_err(|err| format!("encode TOML: {err}"))
+            .and_then(|v| {
+                toml::to_string(&ValuesFirst(&v)).map_err(|err| format!("encode TOML: {err}"))
+            }),
+    }
+}
+
+fn select_field<'a>(
+    v: &'a serde_json::Value,
+    field: &str,
+) -> Result<&'a serde_json::Value, String> {
+    let mut selected = v;
+    for (i, name) in field.split('.').enumerate() {
+        let next = match selected {
+            serde_json::Value::Object(m) => m.get(name),
+            serde_json::Value::Array(v) => name.parse::<usize>().ok().and_then(|i| v.get(i)),
+            _ => None,
+        };
+
+        selected = match next {
+            Some(v) => v,
+            None => {
+                let parent = match field.split('.').take(i).collect::<Vec<_>>().join(".") {
+                    v if v.is_empty() => String::new(),
+                    v => format!(" under '{v}'"),
+                };
+                let expected = match selected {
+                    serde_json::Value::Object(m) => {
+                        m.keys().cloned().collect::<Vec<_>>().join(", ")
+                    }
+                    serde_json::Value::Array(v) => format!("0..{}", v.len()),
+                    _ => "none".to_string(),
+                };
+                let hint = format!("unknown field '{name}'{parent}, expect one of: {expected}");
+                return Err(hint);
+            }
+        };
+    }
+
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTE: &[u8] = include_bytes!("../../testdata/quote.bin");
+    const QUOTE_HEX: &[u8] = include_bytes!("../../testdata/quote.hex");
+
+    fn decode_auto(b: &[u8]) -> Result<Vec<u8>, String> {
+        decode_input(b, InputEncoding::Auto)
+    }
+
+    #[test]
+    fn raw() {
+        assert_eq!(detect_input_encoding(QUOTE), InputEncoding::Raw);
+        assert_eq!(decode_auto(QUOTE).unwrap(), QUOTE);
+    }
+
+    #[test]
+    fn hex() {
+        let want = [0xde, 0xad, 0xbe, 0xef];
+        for v in [
+            "deadbeef",
+            "0xDEADBEEF",
+            "0Xdeadbeef",
+            "de ad\nbe\tef\n",
+            "0xdead\r\nbeef",
+        ] {
+            assert_eq!(
+                detect_input_encoding(v.as_bytes()),
+                InputEncoding::Hex,
+                "{v}"
+            );
+            assert_eq!(decode_auto(v.as_bytes()).unwrap(), want, "{v}");
+        }
+    }
+
+    #[test]
+    fn base64() {
+        let data: Vec<u8> = (0..=255).collect();
+        let std = base64::encode_block(&data);
+        assert!(std.contains('+') && std.contains('/') && std.ends_with('='));
+
+        assert_eq!(detect_input_encoding(std.as_bytes()), InputEncoding::Base64);
+        assert_eq!(decode_auto(std.as_bytes()).unwrap(), data);
+
+        let wrapped: String = std
+            .as_bytes()
+            .chunks(64)
+            .map(|v| format!("{}\n", std::str::from_utf8(v).unwrap()))
+            .collect();
+        assert_eq!(decode_auto(wrapped.as_bytes()).unwrap(), data);
+
+        let url = std.replace('+', "-").replace('/', "_");
+        let url = url.trim_end_matches('=');
+        assert_eq!(
+            detect_input_encoding(url.as_bytes()),
+            InputEncoding::Base64url
+        );
+        assert_eq!(decode_auto(url.as_bytes()).unwrap(), data);
+    }
+
+    #[test]
+    fn pem() {
+        let data: Vec<u8> = (0..=255).collect();
+        let pem = format!(
+            "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+            base64::encode_block(&data)
+        );
+
+        assert_eq!(detect_input_encoding(pem.as_bytes()), InputEncoding::Pem);
+        assert_eq!(decode_auto(pem.as_bytes()).unwrap(), data);
+    }
+
+    #[test]
+    fn hex_over_base64() {
+        // Even-length hex is also valid base64, which is resolved as hex.
+        assert_eq!(detect_input_encoding(QUOTE_HEX), InputEncoding::Hex);
+        assert_eq!(decode_auto(QUOTE_HEX).unwrap(), QUOTE);
+
+        // Odd-length hex is left to base64.
+        assert_eq!(detect_input_encoding(b"abcdefabc"), InputEncoding::Base64);
+    }
+
+    #[test]
+    fn override_detection() {
+        let b = b"deadbeef";
+        assert_eq!(
+            decode_input(b, InputEncoding::Base64).unwrap(),
+            [0x75, 0xe6, 0x9d, 0x6d, 0xe7, 0x9f]
+        );
+        assert_eq!(decode_input(b, InputEncoding::Raw).unwrap(), b);
+        assert!(decode_input(QUOTE, InputEncoding::Hex).is_err());
+    }
+
+    #[test]
+    fn toml_field_order() {
+        #[derive(Serialize)]
+        struct Inner {
+            b: u8,
+            a: u8,
+        }
+
+        #[derive(Serialize)]
+        struct Outer {
+            z: Inner,
+            y: u8,
+            x: Option<u8>,
+            w: Inner,
+        }
+
+        let v = Outer {
+            z: Inner { b: 1, a: 2 },
+            y: 3,
+            x: None,
+            w: Inner { b: 4, a: 5 },
+        };
+
+        let expect = "y = 3\n\n[z]\nb = 1\na = 2\n\n[w]\nb = 4\na = 5\n";
+        assert_eq!(serialize(&v, OutputFormat::Toml).unwrap(), expect);
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::Write;
+
+use serde_json::{Map, Value};
+
+use crate::sgx::Quote3;
+
+/// Sections of `Quote3` in the order of the quote layout. `signature_data_len` is left out as it
+/// follows the signature section.
+const QUOTE_SECTIONS: &[&str] = &["header", "body_descriptor", "body", "signature"];
+
+/// Aspects which differences are summarized by, along with the fields they're told by. A field
+/// matches if its dotted path equals or starts with any of the listed ones.
+const ASPECTS: &[(&str, &[&str])] = &[
+    (
+        "quote format",
+        &[
+            "header.version",
+            "header.att_key_type",
+            "header.att_key_data_0",
+            "body_descriptor",
+        ],
+    ),
+    (
+        "platform TCB",
+        &[
+            "header.qe_svn",
+            "header.pce_svn",
+            "body.cpu_svn",
+            "body.tee_tcb_svn",
+            "signature.qe_report.cpu_svn",
+            "signature.qe_report.isv_svn",
+        ],
+    ),
+    (
+        "platform",
+        &["header.user_data", "signature.certification_data"],
+    ),
+    ("attestation key", &["signature.attest_pub_key"]),
+    ("enclave SVN", &["body.isv_svn"]),
+    (
+        "enclave configuration",
+        &[
+            "body.attributes",
+            "body.misc_select",
+            "body.config_id",
+            "body.config_svn",
+            "body.isv_ext_prod_id",
+            "body.isv_family_id",
+            "body.td_attributes",
+            "body.xfam",
+            "body.mr_config_id",
+            "body.mr_owner",
+            "body.mr_owner_config",
+        ],
+    ),
+    ("runtime measurements", &["body.rt_mr"]),
+    ("report_data", &["body.report_data"]),
+];
+
+/// Fields identifying the enclave or TD, i.e. the measurements of its code and signer.
+const IDENTITY_FIELDS: &[&str] = &[
+    "body.mr_enclave",
+    "body.mr_signer",
+    "body.isv_prod_id",
+    "body.mr_td",
+    "body.mr_seam",
+    "body.mr_signer_seam",
+];
+
+/// Compares two DCAP-based quotes, listing the fields that differ section by section followed by
+/// a summary such as "same enclave, different report_data".
+///
+/// Signatures differ across quotes anyway, so they are listed but left out of the summary.
+pub fn diff_quotes<W>(out: &mut W, a: &[u8], b: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let a = Quote3::try_from(a).map_err(|err| format!("parse quote a: {err}"))?;
+    let b = Quote3::try_from(b).map_err(|err| format!("parse quote b: {err}"))?;
+
+    let a = serde_json::to_value(&a).map_err(|err| format!("encode quote a: {err}"))?;
+    let b = serde_json::to_value(&b).map_err(|err| format!("encode quote b: {err}"))?;
+
+    let mut diffs = vec![];
+    for section in QUOTE_SECTIONS {
+        let mut fields = vec![];
+        diff_values(
+            a.get(section),
+            b.get(section),
+            section.to_string(),
+            &mut fields,
+        );
+        if !fields.is_empty() {
+            diffs.push((section, fields));
+        }
+    }
+
+    let mut paths = vec![];
+    for (section, fields) in diffs.iter() {
+        writeln!(out, "[{section}]").map_err(|err| format!("write: {err}"))?;
+        for (path, a, b) in fields {
+            let name = path
+                .strip_prefix(*section)
+                .and_then(|v| v.strip_prefix('.'))
+                .unwrap_or(path);
+            if let Some(v) = a {
+                writeln!(out, "- {name} = {v}").map_err(|err| format!("write: {err}"))?;
+            }
+            if let Some(v) = b {
+                writeln!(out, "+ {name} = {v}").map_err(|err| format!("write: {err}"))?;
+            }
+            paths.push(path.as_str());
+        }
+        writeln!(out).map_err(|err| format!("write: {err}"))?;
+    }
+
+    writeln!(out, "summary = {}", summarize(&paths)).map_err(|err| format!("write: {err}"))
+}
+
+type FieldDiff = (String, Option<String>, Option<String>);
+
+/// Collects the leaf fields under `path` which differ between `a` and `b`. Objects are walked
+/// into even if present on one side only, while other values including arrays are compared as a
+/// whole.
+fn diff_values(a: Option<&Value>, b: Option<&Value>, path: String, out: &mut Vec<FieldDiff>) {
+    if a == b {
+        return;
+    }
+
+    let empty = Map::new();
+    if let (Some(x), Some(y)) = (fields_of(a, &empty), fields_of(b, &empty)) {
+        let mut keys: Vec<&String> = x.keys().chain(y.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for k in keys {
+            diff_values(x.get(k), y.get(k), format!("{path}.{k}"), out);
+        }
+    } else {
+        out.push((path, a.map(show), b.map(show)));
+    }
+}
+
+/// Fields of an object, where a missing value counts as an object without any field.
+fn fields_of<'a>(
+    v: Option<&'a Value>,
+    empty: &'a Map<String, Value>,
+) -> Option<&'a Map<String, Value>> {
+    match v {
+        Some(Value::Object(v)) => Some(v),
+        None => Some(empty),
+        Some(_) => None,
+    }
+}
+
+fn show(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.replace('\n', "\\n"),
+        v => v.to_string(),
+    }
+}
+
+fn summarize(paths: &[&str]) -> String {
+    if paths.is_empty() {
+        return "identical quotes".to_string();
+    }
+
+    let matches = |prefixes: &[&str]| {
+        paths.iter().any(|p| {
+            prefixes.iter().any(|v| {
+                p.strip_prefix(v)
+                    .map(|rest| rest.is_empty() || rest.starts_with('.'))
+                    .unwrap_or(false)
+            })
+        })
+    };
+
+    let mut out = vec![];
+    if matches(IDENTITY_FIELDS) || matches(&["body_descriptor"]) {
+        out.push("different enclave".to_string());
+    } else {
+        out.push("same enclave".to_string());
+    }
+
+    for (aspect, fields) in ASPECTS {
+        if matches(fields) {
+            out.push(format!("different {aspect}"));
+        }
+    }
+
+    if out.len() == 1 {
+        out.push("only signatures differ".to_string());
+    }
+
+    out.join(", ")
+}
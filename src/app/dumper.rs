@@ -1,21 +1,167 @@
 use std::io::Write;
 
-use crate::sgx::{Quote3, SigStruct};
+use encoding::hex;
 
-pub fn decode_and_dump_quote3<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+use crate::app::codec::{self, decode_hex};
+use crate::app::types::OutputFormat;
+use crate::app::{rsa, verify_report_mac, x509};
+use crate::sgx::{
+    Attributes, EpidQuote, Quote3, QuoteBody, Report, SgxExtensions, SigStruct, SigStructKey,
+    TargetInfo,
+};
+
+/// Dumps the SGX extensions of a PCK certificate in PEM or DER. Only the first certificate is
+/// dumped if a PEM chain is given.
+pub fn decode_and_dump_pck_cert<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let der = if b.starts_with(b"-----BEGIN") {
+        let certs = x509::decode_pem_certs(b).map_err(|err| format!("decode PEM: {err}"))?;
+        let cert = certs.first().ok_or_else(|| "no certificate".to_string())?;
+        cert.to_der().map_err(|err| format!("encode DER: {err}"))?
+    } else {
+        b.to_vec()
+    };
+
+    let ext = SgxExtensions::from_cert_der(&der).map_err(|err| format!("parse: {err}"))?;
+
+    writeln!(out, "{ext}").map_err(|err| format!("dump: {err}"))
+}
+
+/// Dumps the MRSIGNER of enclaves signed by an RSA-3072 key in PEM or DER.
+pub fn decode_and_dump_key_mrsigner<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let key = rsa::decode_rsa_private_key(b).map_err(|err| format!("decode key: {err}"))?;
+
+    let bits = key.n().num_bits();
+    if bits != 3072 {
+        return Err(format!("bad modulus: expect 3072 bits, got {bits}"));
+    }
+    if key.e().to_vec() != [3] {
+        eprintln!("WARNING: public exponent isn't 3 as required by SGX!");
+    }
+
+    let key = SigStructKey {
+        modulus: rsa::bn_to_le(key.n()).map_err(|err| format!("encode modulus: {err}"))?,
+        ..Default::default()
+    };
+
+    writeln!(out, "{}", hex::encode_to_string(key.mr_signer().as_ref()))
+        .map_err(|err| format!("dump: {err}"))
+}
+
+/// Dumps either an EPID-based or a DCAP-based quote, which is told apart by the version field.
+pub fn decode_and_dump_quote<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let version = match b {
+        [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]),
+        _ => return Err("parse: missing version".to_string()),
+    };
+
+    match version {
+        1 | 2 => {
+            let quote = EpidQuote::try_from(b).map_err(|err| format!("parse EPID quote: {err}"))?;
+            warn_if_debug("quote body", &quote.body.attributes);
+            writeln!(out, "{quote}").map_err(|err| format!("dump: {err}"))
+        }
+        _ => decode_and_dump_quote3(out, b, OutputFormat::Text, &[], None),
+    }
+}
+
+/// Dumps a DCAP-based quote, which is tagged with the `file` it's read from if given. Only the
+/// raw values of `fields` are written if any, see [`codec::encode_fields`].
+pub fn decode_and_dump_quote3<W>(
+    out: &mut W,
+    b: &[u8],
+    format: OutputFormat,
+    fields: &[String],
+    file: Option<&str>,
+) -> Result<(), String>
 where
     W: Write,
 {
     let quote = Quote3::try_from(b).map_err(|err| format!("parse: {err}"))?;
+    if let QuoteBody::Sgx(v) = &quote.body {
+        warn_if_debug("quote body", &v.attributes);
+    }
+
+    if !fields.is_empty() {
+        return codec::encode_fields(out, file, &quote, fields)
+            .map_err(|err| format!("dump: {err}"));
+    }
+
+    codec::encode_record(out, file, &quote, format).map_err(|err| format!("dump: {err}"))
+}
+
+/// Dumps a REPORT, whose MAC is verified if the hex-encoded `report_key` is given.
+pub fn decode_and_dump_report<W>(
+    out: &mut W,
+    b: &[u8],
+    report_key: Option<&str>,
+) -> Result<(), String>
+where
+    W: Write,
+{
+    let report = Report::try_from(b).map_err(|err| format!("parse: {err}"))?;
+    warn_if_debug("report body", &report.body.attributes);
+
+    writeln!(out, "{report}").map_err(|err| format!("dump: {err}"))?;
+
+    let report_key = match report_key {
+        Some(v) => decode_hex(v).map_err(|err| format!("decode report key: {err}"))?,
+        None => return Ok(()),
+    };
 
-    writeln!(out, "{quote}").map_err(|err| format!("dump: {err}"))
+    let result = verify_report_mac(&report, &report_key);
+    match &result {
+        Ok(_) => writeln!(out, "\nmac_verification = ok"),
+        Err(err) => writeln!(out, "\nmac_verification = failed: {err}"),
+    }
+    .map_err(|err| format!("dump: {err}"))?;
+
+    result
 }
 
-pub fn decode_and_dump_sig_struct<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+/// Dumps a SIGSTRUCT, which is tagged with the `file` it's read from if given. Only the raw
+/// values of `fields` are written if any, see [`codec::encode_fields`].
+pub fn decode_and_dump_sig_struct<W>(
+    out: &mut W,
+    b: &[u8],
+    format: OutputFormat,
+    fields: &[String],
+    file: Option<&str>,
+) -> Result<(), String>
 where
     W: Write,
 {
     let ss = SigStruct::try_from(b).map_err(|err| format!("parse: {err}"))?;
+    warn_if_debug("SIGSTRUCT", &ss.body.attributes);
+
+    if !fields.is_empty() {
+        return codec::encode_fields(out, file, &ss, fields).map_err(|err| format!("write: {err}"));
+    }
+
+    codec::encode_record(out, file, &ss, format).map_err(|err| format!("write: {err}"))
+}
+
+pub fn decode_and_dump_target_info<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let target_info = TargetInfo::try_from(b).map_err(|err| format!("parse: {err}"))?;
+    warn_if_debug("TARGETINFO", &target_info.attributes);
+
+    writeln!(out, "{target_info}").map_err(|err| format!("dump: {err}"))
+}
 
-    writeln!(out, "{ss}").map_err(|err| format!("write: {err}"))
+/// Warns on stderr so that the warning isn't lost when the dump is redirected.
+fn warn_if_debug(what: &str, attributes: &Attributes) {
+    if attributes.is_debug() {
+        eprintln!("WARNING: DEBUG attribute is set in {what}! The enclave is debuggable and its secrets are NOT protected.");
+    }
 }
@@ -0,0 +1,42 @@
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey, EcPoint};
+use openssl::ecdsa::EcdsaSig;
+use openssl::nid::Nid;
+use openssl::pkey::{HasPublic, Public};
+use openssl::sha;
+
+/// Builds a P-256 public key from the raw concatenation of its x and y coordinates.
+pub fn ecdsa_p256_pubkey_from_raw(xy: &[u8]) -> Result<EcKey<Public>, String> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)
+        .map_err(|err| format!("load P-256 group: {err}"))?;
+    let mut ctx = BigNumContext::new().map_err(|err| format!("new BN context: {err}"))?;
+
+    // Prefix with 0x04 to mark the point as uncompressed.
+    let mut encoded = Vec::with_capacity(1 + xy.len());
+    encoded.push(0x04);
+    encoded.extend_from_slice(xy);
+
+    let point = EcPoint::from_bytes(&group, &encoded, &mut ctx)
+        .map_err(|err| format!("decode point: {err}"))?;
+
+    EcKey::from_public_key(&group, &point).map_err(|err| format!("build key: {err}"))
+}
+
+/// Verifies a raw (r||s) ECDSA signature over the SHA-256 digest of `msg`.
+pub fn verify_ecdsa_p256_sha256<T>(key: &EcKey<T>, raw_sig: &[u8], msg: &[u8]) -> Result<(), String>
+where
+    T: HasPublic,
+{
+    let (r, s) = raw_sig.split_at(raw_sig.len() / 2);
+    let r = BigNum::from_slice(r).map_err(|err| format!("decode r: {err}"))?;
+    let s = BigNum::from_slice(s).map_err(|err| format!("decode s: {err}"))?;
+    let sig =
+        EcdsaSig::from_private_components(r, s).map_err(|err| format!("build signature: {err}"))?;
+
+    let digest = sha::sha256(msg);
+    match sig.verify(&digest, key) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("signature mismatch".to_string()),
+        Err(err) => Err(format!("verify: {err}")),
+    }
+}
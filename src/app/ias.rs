@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use openssl::base64;
+use openssl::hash::MessageDigest;
+use openssl::sign::Verifier;
+use openssl::x509::X509Ref;
+use serde::Deserialize;
+
+use crate::app::x509;
+use crate::sgx::EpidQuote;
+
+/// Attestation verification report returned by Intel Attestation Service for EPID quotes.
+///
+/// ref: https://api.trustedservices.intel.com/documents/sgx-attestation-api-spec.pdf
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IasReport {
+    pub id: String,
+    pub timestamp: String,
+    pub version: u32,
+    pub isv_enclave_quote_status: String,
+    pub isv_enclave_quote_body: String,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    #[serde(default, rename = "advisoryIDs")]
+    pub advisory_ids: Vec<String>,
+    #[serde(default, rename = "advisoryURL")]
+    pub advisory_url: Option<String>,
+    #[serde(default)]
+    pub epid_pseudonym: Option<String>,
+}
+
+impl IasReport {
+    /// Decodes `isvEnclaveQuoteBody`, which is an EPID quote truncated right after the report body.
+    pub fn isv_enclave_quote(&self) -> Result<EpidQuote, String> {
+        let b = base64::decode_block(&self.isv_enclave_quote_body)
+            .map_err(|err| format!("decode base64: {err}"))?;
+
+        EpidQuote::try_from(b.as_slice())
+    }
+
+    /// Verifies the `X-IASReport-Signature` over the raw report body with the report signing
+    /// certificate in `signing_chain`, and the chain itself against the IAS report signing `ca`.
+    pub fn verify_signature(
+        body: &[u8],
+        signature: &[u8],
+        signing_chain: &[u8],
+        ca: &X509Ref,
+    ) -> Result<(), String> {
+        let signing_chain = percent_decode(signing_chain);
+        let chain = x509::decode_pem_certs(&signing_chain)
+            .map_err(|err| format!("decode signing chain: {err}"))?;
+        x509::verify_cert_chain(&chain, ca, &[])
+            .map_err(|err| format!("verify signing chain: {err}"))?;
+
+        let signature: String = String::from_utf8_lossy(signature)
+            .chars()
+            .filter(|v| !v.is_ascii_whitespace())
+            .collect();
+        let signature =
+            base64::decode_block(&signature).map_err(|err| format!("decode signature: {err}"))?;
+
+        let key = chain[0]
+            .public_key()
+            .map_err(|err| format!("load signing key: {err}"))?;
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key)
+            .map_err(|err| format!("new verifier: {err}"))?;
+        match verifier.verify_oneshot(&signature, body) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("signature mismatch".to_string()),
+            Err(err) => Err(format!("verify signature: {err}")),
+        }
+    }
+}
+
+impl Display for IasReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |v: &Option<String>| -> String { v.clone().unwrap_or_else(|| "-".to_string()) };
+
+        writeln!(f, "id = {}", self.id).expect("write id");
+        writeln!(f, "timestamp = {}", self.timestamp).expect("write timestamp");
+        writeln!(f, "version = {}", self.version).expect("write version");
+        writeln!(
+            f,
+            "isv_enclave_quote_status = {}",
+            self.isv_enclave_quote_status
+        )
+        .expect("write isv_enclave_quote_status");
+        writeln!(f, "advisory_ids = {:?}", self.advisory_ids).expect("write advisory_ids");
+        writeln!(f, "advisory_url = {}", show(&self.advisory_url)).expect("write advisory_url");
+        writeln!(f, "nonce = {}", show(&self.nonce)).expect("write nonce");
+        write!(f, "epid_pseudonym = {}", show(&self.epid_pseudonym)).expect("write epid_pseudonym");
+
+        Ok(())
+    }
+}
+
+/// The signing certificate chain is URL-encoded in the `X-IASReport-Signing-Certificate` header,
+/// which is decoded here. PEM without any '%' is returned as is.
+fn percent_decode(b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(b.len());
+
+    let mut i = 0;
+    while i < b.len() {
+        let decoded = match b[i..] {
+            [b'%', hi, lo, ..] => {
+                let hex = [hi, lo];
+                std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|v| u8::from_str_radix(v, 16).ok())
+            }
+            _ => None,
+        };
+
+        match decoded {
+            Some(v) => {
+                out.push(v);
+                i += 3;
+            }
+            None => {
+                out.push(b[i]);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
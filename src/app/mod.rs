@@ -1,9 +1,27 @@
 mod checker;
+mod codec;
+mod differ;
 mod dumper;
+mod ecdsa;
 mod generate_key;
+mod ias;
+mod measure;
+mod policy;
+mod rsa;
+mod signer;
+mod target_info;
+mod tcb;
+mod verifier;
+mod x509;
 
 pub mod types;
 
 pub use checker::*;
+pub use codec::{decode_hex, decode_input, write_record_header};
+pub use differ::diff_quotes;
 pub use dumper::*;
 pub use generate_key::generate_and_encode_key;
+pub use measure::measure_enclave;
+pub use signer::{finalize_sig_struct, prepare_sig_struct, sign_and_encode_sig_struct};
+pub use target_info::build_and_encode_target_info;
+pub use verifier::*;
@@ -0,0 +1,133 @@
+use encoding::hex;
+use serde::Deserialize;
+
+use crate::app::codec::decode_hex;
+use crate::sgx::ReportBody;
+
+/// Expected values of a report body, which is loaded from a TOML file such as
+///
+/// ```toml
+/// mr_enclave = ["7f71691ecfae8db7001cc8b159241857e17476c78b3b9420064625b8df6954c8"]
+/// isv_prod_id = 1
+/// min_isv_svn = 2
+/// forbidden_attributes = 0x02 # DEBUG
+/// ```
+///
+/// Rules absent from the file are skipped.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Allowed hex-encoded MRENCLAVE values.
+    pub mr_enclave: Option<Vec<String>>,
+    /// Allowed hex-encoded MRSIGNER values.
+    pub mr_signer: Option<Vec<String>>,
+    pub isv_prod_id: Option<u16>,
+    pub min_isv_svn: Option<u16>,
+    /// Bits of the ATTRIBUTES flags which must be set.
+    pub required_attributes: Option<u64>,
+    /// Bits of the ATTRIBUTES flags which must be cleared.
+    pub forbidden_attributes: Option<u64>,
+    /// Expected XFRM, compared under `xfrm_mask`.
+    pub xfrm: Option<u64>,
+    /// Bits of XFRM to compare against `xfrm`, which defaults to all bits.
+    pub xfrm_mask: Option<u64>,
+    pub min_config_svn: Option<u16>,
+    /// Hex-encoded prefix of the report data.
+    pub report_data_prefix: Option<String>,
+}
+
+impl Policy {
+    /// Checks `body` against every rule specified in the policy, returning the outcome of each
+    /// rule along with its name.
+    pub fn appraise(&self, body: &ReportBody) -> Vec<(&'static str, Result<(), String>)> {
+        let mut out = vec![];
+
+        if let Some(v) = &self.mr_enclave {
+            out.push(("mr_enclave", check_allowed(v, &body.mr_enclave)));
+        }
+
+        if let Some(v) = &self.mr_signer {
+            out.push(("mr_signer", check_allowed(v, &body.mr_signer)));
+        }
+
+        if let Some(expected) = self.isv_prod_id {
+            let got = body.isv_prod_id;
+            out.push((
+                "isv_prod_id",
+                check(got == expected, || format!("got {got}")),
+            ));
+        }
+
+        if let Some(minimum) = self.min_isv_svn {
+            let got = body.isv_svn;
+            let result = check(got >= minimum, || format!("isv_svn {got} < {minimum}"));
+            out.push(("min_isv_svn", result));
+        }
+
+        let flags = body.attributes.flags;
+        if let Some(v) = self.required_attributes {
+            let missing = v & !flags;
+            let result = check(missing == 0, || format!("missing bits {missing:#x}"));
+            out.push(("required_attributes", result));
+        }
+
+        if let Some(v) = self.forbidden_attributes {
+            let present = v & flags;
+            let result = check(present == 0, || format!("present bits {present:#x}"));
+            out.push(("forbidden_attributes", result));
+        }
+
+        if let Some(expected) = self.xfrm {
+            let mask = self.xfrm_mask.unwrap_or(u64::MAX);
+            let got = body.attributes.xfrm;
+            let result = check((got & mask) == (expected & mask), || {
+                format!("got {got:#x} under mask {mask:#x}, expect {expected:#x}")
+            });
+            out.push(("xfrm", result));
+        }
+
+        if let Some(minimum) = self.min_config_svn {
+            let got = body.config_svn;
+            let result = check(got >= minimum, || format!("config_svn {got} < {minimum}"));
+            out.push(("min_config_svn", result));
+        }
+
+        if let Some(v) = &self.report_data_prefix {
+            let result = decode_hex(v)
+                .map_err(|err| format!("decode prefix: {err}"))
+                .and_then(|prefix| {
+                    let got = &body.report_data;
+                    check(got.starts_with(&prefix), || {
+                        let got = &got[..prefix.len().min(got.len())];
+                        format!("got {}", hex::encode_to_string(got))
+                    })
+                });
+            out.push(("report_data_prefix", result));
+        }
+
+        out
+    }
+}
+
+fn check<F>(ok: bool, hint: F) -> Result<(), String>
+where
+    F: FnOnce() -> String,
+{
+    if ok {
+        Ok(())
+    } else {
+        Err(hint())
+    }
+}
+
+fn check_allowed(allowed: &[String], got: &[u8]) -> Result<(), String> {
+    for (i, v) in allowed.iter().enumerate() {
+        let v = decode_hex(v).map_err(|err| format!("decode #{i}: {err}"))?;
+        if v == got {
+            return Ok(());
+        }
+    }
+
+    let hint = format!("{} isn't allowed", hex::encode_to_string(got));
+    Err(hint)
+}
@@ -0,0 +1,108 @@
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private, Public};
+use openssl::rsa::Rsa;
+use openssl::sign::Verifier;
+
+/// Length in bytes of RSA-3072 values, i.e. the modulus, the signature, q1 and q2 of SIGSTRUCTs.
+pub const LENGTH_RSA3072: usize = 384;
+
+/// Decodes an RSA private key in PEM or DER, such as those by `generate-key`.
+pub fn decode_rsa_private_key(b: &[u8]) -> Result<Rsa<Private>, String> {
+    let key = if b.starts_with(b"-----BEGIN") {
+        PKey::private_key_from_pem(b).map_err(|err| format!("decode PEM: {err}"))?
+    } else {
+        PKey::private_key_from_der(b).map_err(|err| format!("decode DER: {err}"))?
+    };
+
+    key.rsa().map_err(|err| format!("not an RSA key: {err}"))
+}
+
+/// Decodes an RSA public key in PEM or DER, either as a SubjectPublicKeyInfo or in PKCS#1.
+pub fn decode_rsa_public_key(b: &[u8]) -> Result<Rsa<Public>, String> {
+    let key = if b.starts_with(b"-----BEGIN RSA PUBLIC KEY") {
+        Rsa::public_key_from_pem_pkcs1(b).map_err(|err| format!("decode PKCS#1 PEM: {err}"))?
+    } else if b.starts_with(b"-----BEGIN") {
+        Rsa::public_key_from_pem(b).map_err(|err| format!("decode PEM: {err}"))?
+    } else {
+        Rsa::public_key_from_der(b)
+            .or_else(|_| Rsa::public_key_from_der_pkcs1(b))
+            .map_err(|err| format!("decode DER: {err}"))?
+    };
+
+    Ok(key)
+}
+
+/// Decodes a little-endian unsigned integer, which is how SIGSTRUCTs store RSA values.
+pub fn bn_from_le(b: &[u8]) -> Result<BigNum, String> {
+    let mut be = b.to_vec();
+    be.reverse();
+
+    BigNum::from_slice(&be).map_err(|err| format!("decode integer: {err}"))
+}
+
+/// Encodes an unsigned integer as `N` bytes in little endian.
+pub fn bn_to_le<const N: usize>(v: &BigNumRef) -> Result<[u8; N], String> {
+    let mut b = v
+        .to_vec_padded(N as i32)
+        .map_err(|err| format!("encode integer: {err}"))?;
+    b.reverse();
+
+    Ok(b.try_into().expect("padded to N bytes"))
+}
+
+/// Builds an RSA public key from the little-endian modulus and exponent of a SIGSTRUCT.
+pub fn rsa_pubkey_from_le(modulus: &[u8], exponent: &[u8]) -> Result<Rsa<Public>, String> {
+    let n = bn_from_le(modulus).map_err(|err| format!("decode modulus: {err}"))?;
+    let e = bn_from_le(exponent).map_err(|err| format!("decode exponent: {err}"))?;
+
+    Rsa::from_public_components(n, e).map_err(|err| format!("build key: {err}"))
+}
+
+/// Verifies a little-endian RSASSA-PKCS1-v1_5 signature over the SHA-256 digest of `msg`.
+pub fn verify_rsa_sha256(key: &Rsa<Public>, sig_le: &[u8], msg: &[u8]) -> Result<(), String> {
+    let mut sig = sig_le.to_vec();
+    sig.reverse();
+
+    let key = PKey::from_rsa(key.clone()).map_err(|err| format!("RSA key as EVP_PKEY: {err}"))?;
+    let mut verifier = Verifier::new(MessageDigest::sha256(), &key)
+        .map_err(|err| format!("new verifier: {err}"))?;
+    match verifier.verify_oneshot(&sig, msg) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("signature mismatch".to_string()),
+        Err(err) => Err(format!("verify: {err}")),
+    }
+}
+
+/// Computes q1 and q2 of a SIGSTRUCT in little endian, which help EINIT verify the signature
+/// without divisions, i.e. q1 = floor(s^2 / n) and q2 = floor((s^3 - q1 * s * n) / n).
+pub fn compute_q1_q2(
+    signature: &BigNumRef,
+    modulus: &BigNumRef,
+) -> Result<([u8; LENGTH_RSA3072], [u8; LENGTH_RSA3072]), String> {
+    let err = |err: ErrorStack| format!("compute: {err}");
+    let mut ctx = BigNumContext::new().map_err(err)?;
+
+    let mut s2 = BigNum::new().map_err(err)?;
+    s2.checked_mul(signature, signature, &mut ctx)
+        .map_err(err)?;
+    let mut q1 = BigNum::new().map_err(err)?;
+    q1.checked_div(&s2, modulus, &mut ctx).map_err(err)?;
+
+    let mut s3 = BigNum::new().map_err(err)?;
+    s3.checked_mul(&s2, signature, &mut ctx).map_err(err)?;
+    let mut q1s = BigNum::new().map_err(err)?;
+    q1s.checked_mul(&q1, signature, &mut ctx).map_err(err)?;
+    let mut q1sn = BigNum::new().map_err(err)?;
+    q1sn.checked_mul(&q1s, modulus, &mut ctx).map_err(err)?;
+    let mut rem = BigNum::new().map_err(err)?;
+    rem.checked_sub(&s3, &q1sn).map_err(err)?;
+    let mut q2 = BigNum::new().map_err(err)?;
+    q2.checked_div(&rem, modulus, &mut ctx).map_err(err)?;
+
+    let q1 = bn_to_le(&q1).map_err(|err| format!("encode q1: {err}"))?;
+    let q2 = bn_to_le(&q2).map_err(|err| format!("encode q2: {err}"))?;
+
+    Ok((q1, q2))
+}
@@ -0,0 +1,273 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use openssl::bn::BigNumRef;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+use crate::app::rsa;
+use crate::app::types::EnclaveSigningParams;
+use crate::sgx::{SigStruct, SigStructBody, SigStructHeader, SigStructKey};
+use crate::sgx::{ATTRIBUTE_DEBUG, SIG_STRUCT_HEADER, SIG_STRUCT_HEADER2, SIG_STRUCT_TYPE_DEBUG};
+
+/// Signs the enclave described by `params` with the RSA-3072 key in PEM or DER, and writes out
+/// the SIGSTRUCT as raw bytes if `raw` is set, or dumps it otherwise.
+pub fn sign_and_encode_sig_struct<W>(
+    w: &mut W,
+    params: &EnclaveSigningParams,
+    key: &[u8],
+    raw: bool,
+) -> Result<(), String>
+where
+    W: Write,
+{
+    let key = rsa::decode_rsa_private_key(key).map_err(|err| format!("decode key: {err}"))?;
+    if key.n().num_bits() != 3072 || key.e().to_vec() != [3] {
+        return Err("key isn't RSA-3072 with exponent 3".to_string());
+    }
+
+    let mut ss = new_unsigned_sig_struct(params)?;
+
+    let pkey = PKey::from_rsa(key.clone()).map_err(|err| format!("RSA key as EVP_PKEY: {err}"))?;
+    let mut signer =
+        Signer::new(MessageDigest::sha256(), &pkey).map_err(|err| format!("new signer: {err}"))?;
+    let mut signature = signer
+        .sign_oneshot_to_vec(&ss.signed_message())
+        .map_err(|err| format!("sign: {err}"))?;
+    signature.reverse();
+
+    attach_signature(&mut ss, key.n(), &signature)?;
+
+    if raw {
+        w.write_all(ss.as_ref())
+    } else {
+        writeln!(w, "{ss}")
+    }
+    .map_err(|err| format!("write: {err}"))
+}
+
+/// Prepares the SIGSTRUCT of the enclave described by `params` for signing elsewhere, such as by
+/// an HSM, i.e. writes the message to sign to `data_out` and the SIGSTRUCT without the key,
+/// signature and q1/q2 to `sig_struct_out`. The message is the header followed by the body, which
+/// is to be signed with RSASSA-PKCS1-v1_5 and SHA-256.
+pub fn prepare_sig_struct<W1, W2>(
+    data_out: &mut W1,
+    sig_struct_out: &mut W2,
+    params: &EnclaveSigningParams,
+) -> Result<(), String>
+where
+    W1: Write,
+    W2: Write,
+{
+    let ss = new_unsigned_sig_struct(params)?;
+
+    data_out
+        .write_all(&ss.signed_message())
+        .map_err(|err| format!("write signing data: {err}"))?;
+    sig_struct_out
+        .write_all(ss.as_ref())
+        .map_err(|err| format!("write SIGSTRUCT: {err}"))
+}
+
+/// Finalizes a SIGSTRUCT prepared by [`prepare_sig_struct`] with the big-endian `signature` made
+/// externally and the RSA-3072 public key in PEM or DER, i.e. fills in the key, the signature and
+/// q1/q2 after verifying the signature, and writes out the raw SIGSTRUCT.
+pub fn finalize_sig_struct<W>(
+    w: &mut W,
+    sig_struct: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> Result<(), String>
+where
+    W: Write,
+{
+    let mut ss =
+        SigStruct::try_from(sig_struct).map_err(|err| format!("parse SIGSTRUCT: {err}"))?;
+
+    let key = rsa::decode_rsa_public_key(public_key)
+        .map_err(|err| format!("decode public key: {err}"))?;
+    if key.n().num_bits() != 3072 || key.e().to_vec() != [3] {
+        return Err("key isn't RSA-3072 with exponent 3".to_string());
+    }
+
+    if signature.len() != rsa::LENGTH_RSA3072 {
+        let hint = format!(
+            "bad signature length: expect {}, got {}",
+            rsa::LENGTH_RSA3072,
+            signature.len()
+        );
+        return Err(hint);
+    }
+    let mut signature = signature.to_vec();
+    signature.reverse();
+
+    rsa::verify_rsa_sha256(&key, &signature, &ss.signed_message())
+        .map_err(|err| format!("verify signature: {err}"))?;
+
+    attach_signature(&mut ss, key.n(), &signature)?;
+
+    w.write_all(ss.as_ref())
+        .map_err(|err| format!("write: {err}"))
+}
+
+/// Builds a SIGSTRUCT for `params` whose key, signature and q1/q2 are left empty.
+pub fn new_unsigned_sig_struct(params: &EnclaveSigningParams) -> Result<SigStruct, String> {
+    let (year, month, day) = match params.date {
+        Some(v) => v,
+        None => today()?,
+    };
+
+    let mut attributes = params.attributes;
+    let mut type_ = 0;
+    if params.debug {
+        attributes.flags |= ATTRIBUTE_DEBUG;
+        type_ |= SIG_STRUCT_TYPE_DEBUG;
+    }
+
+    let header = SigStructHeader {
+        header: SIG_STRUCT_HEADER,
+        type_,
+        date: (year as u32) | ((month as u32) << 16) | ((day as u32) << 24),
+        header2: SIG_STRUCT_HEADER2,
+        ..Default::default()
+    };
+
+    let body = SigStructBody {
+        misc_select: params.misc_select,
+        misc_mask: params.misc_mask,
+        attributes,
+        attribute_mask: params.attribute_mask,
+        enclave_hash: params.mr_enclave,
+        isv_prod_id: params.isv_prod_id,
+        isv_svn: params.isv_svn,
+        ..Default::default()
+    };
+
+    let out = SigStruct {
+        header,
+        body,
+        ..Default::default()
+    };
+
+    Ok(out)
+}
+
+/// Fills in the key, the little-endian `signature` and q1/q2 derived from them.
+pub fn attach_signature(
+    ss: &mut SigStruct,
+    modulus: &BigNumRef,
+    signature: &[u8],
+) -> Result<(), String> {
+    let key = SigStructKey {
+        modulus: rsa::bn_to_le(modulus).map_err(|err| format!("encode modulus: {err}"))?,
+        exponent: 3u32.to_le_bytes(),
+        signature: signature
+            .try_into()
+            .map_err(|_| format!("bad signature length: {}", signature.len()))?,
+    };
+
+    let s = rsa::bn_from_le(signature).map_err(|err| format!("decode signature: {err}"))?;
+    let (q1, q2) = rsa::compute_q1_q2(&s, modulus)?;
+
+    ss.key = key;
+    ss.buffer.q1 = q1;
+    ss.buffer.q2 = q2;
+
+    Ok(())
+}
+
+/// Today's date in UTC as (year, month, day).
+fn today() -> Result<(u16, u8, u8), String> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| format!("get time: {err}"))?
+        .as_secs();
+
+    // Converts days since the epoch into a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    Ok((year as u16, month as u8, day as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::bn::{BigNum, BigNumContext};
+    use openssl::rsa::Rsa;
+
+    use crate::app::verifier::verify_sig_struct;
+    use crate::sgx::{Attributes, MiscSelect};
+
+    use super::*;
+
+    #[test]
+    fn sign_then_verify() {
+        let e = BigNum::from_u32(3).unwrap();
+        let key = Rsa::generate_with_e(3072, &e).unwrap();
+        let key = key.private_key_to_pem().unwrap();
+
+        let params = EnclaveSigningParams {
+            mr_enclave: [0x5a; 32],
+            attributes: Attributes {
+                flags: 0x04,
+                xfrm: 0x03,
+            },
+            attribute_mask: Attributes {
+                flags: u64::MAX,
+                xfrm: u64::MAX,
+            },
+            misc_select: MiscSelect(0),
+            misc_mask: MiscSelect(u32::MAX),
+            isv_prod_id: 1,
+            isv_svn: 2,
+            debug: true,
+            date: Some((2023, 2, 15)),
+        };
+
+        let mut out = vec![];
+        sign_and_encode_sig_struct(&mut out, &params, &key, true).unwrap();
+        assert_eq!(out.len(), 1808);
+
+        verify_sig_struct(&mut vec![], &out).unwrap();
+
+        let ss = SigStruct::try_from(out.as_slice()).unwrap();
+        let (type_, date) = (ss.header.type_, ss.header.date);
+        assert_eq!(type_, SIG_STRUCT_TYPE_DEBUG);
+        assert_eq!(date, 2023 | (2 << 16) | (15 << 24));
+        let (flags, enclave_hash) = (ss.body.attributes.flags, ss.body.enclave_hash);
+        assert_eq!(flags, 0x04 | ATTRIBUTE_DEBUG);
+        assert_eq!(enclave_hash, params.mr_enclave);
+
+        // s^2 = q1 * n + r and s * r = q2 * n + r', where 0 <= r, r' < n.
+        let mut ctx = BigNumContext::new().unwrap();
+        let s = rsa::bn_from_le(&ss.key.signature).unwrap();
+        let n = rsa::bn_from_le(&ss.key.modulus).unwrap();
+        let q1 = rsa::bn_from_le(&ss.buffer.q1).unwrap();
+        let q2 = rsa::bn_from_le(&ss.buffer.q2).unwrap();
+
+        let mut s2 = BigNum::new().unwrap();
+        s2.checked_mul(&s, &s, &mut ctx).unwrap();
+        let mut q1n = BigNum::new().unwrap();
+        q1n.checked_mul(&q1, &n, &mut ctx).unwrap();
+        let mut r = BigNum::new().unwrap();
+        r.checked_sub(&s2, &q1n).unwrap();
+        assert!(!r.is_negative() && r < n);
+
+        let mut sr = BigNum::new().unwrap();
+        sr.checked_mul(&s, &r, &mut ctx).unwrap();
+        let mut q2n = BigNum::new().unwrap();
+        q2n.checked_mul(&q2, &n, &mut ctx).unwrap();
+        let mut r2 = BigNum::new().unwrap();
+        r2.checked_sub(&sr, &q2n).unwrap();
+        assert!(!r2.is_negative() && r2 < n);
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use crate::app::types::TargetInfoSource;
+use crate::sgx::{EpidQuote, Quote3, QuoteBody, SigStruct, TargetInfo};
+
+/// Builds a TARGETINFO targeting the enclave described by `b`, and writes it out as raw bytes if
+/// `raw` is set, or dumps it otherwise.
+pub fn build_and_encode_target_info<W>(
+    w: &mut W,
+    b: &[u8],
+    source: TargetInfoSource,
+    raw: bool,
+) -> Result<(), String>
+where
+    W: Write,
+{
+    let target_info = match source {
+        TargetInfoSource::SigStruct => {
+            let ss = SigStruct::try_from(b).map_err(|err| format!("parse SIGSTRUCT: {err}"))?;
+            TargetInfo::from(&ss)
+        }
+        TargetInfoSource::Quote => target_info_from_quote(b)?,
+    };
+
+    if raw {
+        w.write_all(target_info.as_ref())
+    } else {
+        writeln!(w, "{target_info}")
+    }
+    .map_err(|err| format!("write: {err}"))
+}
+
+fn target_info_from_quote(b: &[u8]) -> Result<TargetInfo, String> {
+    let version = match b {
+        [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]),
+        _ => return Err("parse quote: missing version".to_string()),
+    };
+
+    if matches!(version, 1 | 2) {
+        let quote = EpidQuote::try_from(b).map_err(|err| format!("parse EPID quote: {err}"))?;
+        return Ok(TargetInfo::from(&quote.body));
+    }
+
+    let quote = Quote3::try_from(b).map_err(|err| format!("parse quote: {err}"))?;
+    match &quote.body {
+        QuoteBody::Sgx(v) => Ok(TargetInfo::from(v)),
+        _ => Err("quote body isn't an SGX report body".to_string()),
+    }
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use openssl::asn1::Asn1Time;
+use openssl::x509::{X509Crl, X509Ref};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use crate::app::codec::decode_hex;
+use crate::app::ecdsa::verify_ecdsa_p256_sha256;
+use crate::app::x509;
+use crate::sgx::{PckTcb, ReportBody, SgxExtensions, TEE_TYPE_SGX, TEE_TYPE_TDX};
+
+/// QE identity, i.e. the `enclaveIdentity` collateral served by Intel PCS.
+///
+/// ref: https://api.portal.trustedservices.intel.com/documentation#pcs-enclave-identity-v4
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnclaveIdentity {
+    /// Absent before version 2.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub next_update: String,
+    pub miscselect: String,
+    pub miscselect_mask: String,
+    pub attributes: String,
+    pub attributes_mask: String,
+    pub mrsigner: String,
+    pub isvprodid: u16,
+    pub tcb_levels: Vec<EnclaveTcbLevel>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnclaveTcbLevel {
+    pub tcb: EnclaveTcb,
+    pub tcb_status: TcbStatus,
+    #[serde(default, rename = "advisoryIDs")]
+    pub advisory_ids: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct EnclaveTcb {
+    pub isvsvn: u16,
+}
+
+/// Result of evaluating a quote against the TCB info and QE identity.
+#[derive(Clone, Debug)]
+pub struct TcbEvaluation {
+    pub status: TcbStatus,
+    pub advisory_ids: Vec<String>,
+}
+
+/// Platform TCB info, i.e. the `tcbInfo` collateral served by Intel PCS.
+///
+/// ref: https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-v4
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcbInfo {
+    /// TEE type of the TCB info, i.e. "SGX" or "TDX", which is absent before version 3.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub version: u32,
+    pub next_update: String,
+    pub fmspc: String,
+    pub pce_id: String,
+    pub tcb_levels: Vec<TcbLevel>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcbLevel {
+    pub tcb: Tcb,
+    pub tcb_status: TcbStatus,
+    #[serde(default, rename = "advisoryIDs")]
+    pub advisory_ids: Vec<String>,
+}
+
+/// TCB of a level in [`TcbInfo`]. Version 3 lists the SGX components in `sgxtcbcomponents`,
+/// whereas version 2 spells them as `sgxtcbcomp01svn` to `sgxtcbcomp16svn`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Tcb {
+    #[serde(default)]
+    pub sgxtcbcomponents: Vec<TcbComponent>,
+    pub pcesvn: u16,
+    #[serde(flatten)]
+    pub others: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct TcbComponent {
+    pub svn: u8,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum TcbStatus {
+    UpToDate,
+    SWHardeningNeeded,
+    ConfigurationNeeded,
+    ConfigurationAndSWHardeningNeeded,
+    OutOfDate,
+    OutOfDateConfigurationNeeded,
+    Revoked,
+}
+
+#[derive(Deserialize)]
+struct SignedEnclaveIdentity<'a> {
+    #[serde(borrow, rename = "enclaveIdentity")]
+    body: &'a RawValue,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct SignedTcbInfo<'a> {
+    #[serde(borrow, rename = "tcbInfo")]
+    body: &'a RawValue,
+    signature: String,
+}
+
+impl EnclaveIdentity {
+    /// Matches the QE report against the identity, and returns the TCB level it falls into.
+    pub fn evaluate(&self, qe_report: &ReportBody) -> Result<&EnclaveTcbLevel, String> {
+        match self.id.as_deref() {
+            None | Some("QE") => {}
+            Some(v) => return Err(format!("bad id: expect QE, got {v}")),
+        }
+
+        let (misc_select, attributes) = (qe_report.misc_select.0, qe_report.attributes);
+
+        let misc_select_mask = decode_hex_u32(&self.miscselect_mask)
+            .map_err(|err| format!("decode miscselectMask: {err}"))?;
+        let expected =
+            decode_hex_u32(&self.miscselect).map_err(|err| format!("decode miscselect: {err}"))?;
+        if (misc_select & misc_select_mask) != expected {
+            return Err(format!("misc_select mismatch: expect {expected:#010x}"));
+        }
+
+        let mut got = [0u8; 16];
+        got[..8].copy_from_slice(&attributes.flags.to_le_bytes());
+        got[8..].copy_from_slice(&attributes.xfrm.to_le_bytes());
+        let mask = decode_hex(&self.attributes_mask)
+            .map_err(|err| format!("decode attributesMask: {err}"))?;
+        let expected =
+            decode_hex(&self.attributes).map_err(|err| format!("decode attributes: {err}"))?;
+        if mask.len() != 16 || expected.len() != 16 {
+            return Err("attributes and attributesMask must be 16 bytes".to_string());
+        }
+        if got
+            .iter()
+            .zip(mask.iter())
+            .zip(expected.iter())
+            .any(|((g, m), e)| (g & m) != *e)
+        {
+            return Err(format!("attributes mismatch: expect {}", self.attributes));
+        }
+
+        let mrsigner =
+            decode_hex(&self.mrsigner).map_err(|err| format!("decode mrsigner: {err}"))?;
+        if mrsigner.as_slice() != qe_report.mr_signer.as_ref() {
+            return Err(format!("mr_signer mismatch: expect {}", self.mrsigner));
+        }
+
+        let isv_prod_id = qe_report.isv_prod_id;
+        if isv_prod_id != self.isvprodid {
+            return Err(format!("isv_prod_id mismatch: expect {}", self.isvprodid));
+        }
+
+        let isv_svn = qe_report.isv_svn;
+        self.tcb_levels
+            .iter()
+            .find(|v| v.tcb.isvsvn <= isv_svn)
+            .ok_or_else(|| format!("no TCB level matches isv_svn {isv_svn}"))
+    }
+
+    /// Decodes the signed `enclaveIdentity` JSON, verifying its signature with the TCB signing
+    /// certificate in `signing_chain`, and the chain itself against `root`.
+    pub fn decode_and_verify(
+        b: &[u8],
+        signing_chain: &[u8],
+        root: &X509Ref,
+        crls: &[X509Crl],
+    ) -> Result<Self, String> {
+        let signed: SignedEnclaveIdentity =
+            serde_json::from_slice(b).map_err(|err| format!("decode JSON: {err}"))?;
+
+        verify_signed_json(signed.body, &signed.signature, signing_chain, root, crls)?;
+
+        let out: Self =
+            serde_json::from_str(signed.body.get()).map_err(|err| format!("decode body: {err}"))?;
+        verify_not_expired(&out.next_update)?;
+
+        Ok(out)
+    }
+}
+
+impl Tcb {
+    pub fn component_svns(&self) -> Result<[u8; 16], String> {
+        let mut out = [0u8; 16];
+
+        if !self.sgxtcbcomponents.is_empty() {
+            if self.sgxtcbcomponents.len() != out.len() {
+                let hint = format!(
+                    "bad sgxtcbcomponents length: expect 16, got {}",
+                    self.sgxtcbcomponents.len()
+                );
+                return Err(hint);
+            }
+            for (v, c) in out.iter_mut().zip(self.sgxtcbcomponents.iter()) {
+                *v = c.svn;
+            }
+            return Ok(out);
+        }
+
+        for (i, v) in out.iter_mut().enumerate() {
+            let k = format!("sgxtcbcomp{:02}svn", i + 1);
+            *v = self
+                .others
+                .get(&k)
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u8::try_from(v).ok())
+                .ok_or_else(|| format!("missing or bad {k}"))?;
+        }
+
+        Ok(out)
+    }
+
+    /// Whether the PCK's TCB is higher than or equal to this one.
+    pub fn is_covered_by(&self, tcb: &PckTcb) -> Result<bool, String> {
+        let svns = self.component_svns()?;
+
+        let ok = svns.iter().zip(tcb.components.iter()).all(|(v, c)| v <= c)
+            && (self.pcesvn <= tcb.pce_svn);
+        Ok(ok)
+    }
+}
+
+impl TcbEvaluation {
+    /// Evaluates the platform TCB with PCK certificate's SGX extensions, and converges it with the
+    /// QE's TCB level as Intel's QVL does. `tee_type` is the one in the quote header.
+    pub fn new(
+        tcb_info: &TcbInfo,
+        tee_type: u32,
+        pck: &SgxExtensions,
+        qe_identity: &EnclaveIdentity,
+        qe_report: &ReportBody,
+    ) -> Result<Self, String> {
+        let platform = tcb_info
+            .evaluate(tee_type, pck)
+            .map_err(|err| format!("evaluate platform TCB: {err}"))?;
+        let qe = qe_identity
+            .evaluate(qe_report)
+            .map_err(|err| format!("evaluate QE identity: {err}"))?;
+
+        let status = match (qe.tcb_status, platform.tcb_status) {
+            (TcbStatus::Revoked, _) => TcbStatus::Revoked,
+            (TcbStatus::OutOfDate, TcbStatus::UpToDate | TcbStatus::SWHardeningNeeded) => {
+                TcbStatus::OutOfDate
+            }
+            (
+                TcbStatus::OutOfDate,
+                TcbStatus::ConfigurationNeeded | TcbStatus::ConfigurationAndSWHardeningNeeded,
+            ) => TcbStatus::OutOfDateConfigurationNeeded,
+            (_, v) => v,
+        };
+
+        let mut advisory_ids = platform.advisory_ids.clone();
+        for v in qe.advisory_ids.iter() {
+            if !advisory_ids.contains(v) {
+                advisory_ids.push(v.clone());
+            }
+        }
+
+        Ok(Self {
+            status,
+            advisory_ids,
+        })
+    }
+}
+
+impl Display for TcbEvaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ids = self
+            .advisory_ids
+            .iter()
+            .map(|v| format!("{v:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(f, "tcb_status   = {}", self.status).expect("write tcb_status");
+        write!(f, "advisory_ids = [{ids}]").expect("write advisory_ids");
+
+        Ok(())
+    }
+}
+
+impl TcbInfo {
+    /// Returns the first TCB level covered by the PCK certificate, after checking the TCB info is
+    /// for the TEE type of the quote and the certificate belongs to the platform it describes.
+    pub fn evaluate(&self, tee_type: u32, pck: &SgxExtensions) -> Result<&TcbLevel, String> {
+        let expected = match tee_type {
+            TEE_TYPE_SGX => "SGX",
+            TEE_TYPE_TDX => "TDX",
+            v => return Err(format!("unsupported TEE type: {v:#010x}")),
+        };
+        match self.id.as_deref() {
+            Some(v) if v == expected => {}
+            Some(v) => return Err(format!("bad id: expect {expected}, got {v}")),
+            // TCB info before version 3 only covers SGX.
+            None if self.version < 3 && tee_type == TEE_TYPE_SGX => {}
+            None => {
+                let hint = format!("missing id for {expected} in version {}", self.version);
+                return Err(hint);
+            }
+        }
+
+        let fmspc = decode_hex(&self.fmspc).map_err(|err| format!("decode fmspc: {err}"))?;
+        if fmspc.as_slice() != pck.fmspc.as_ref() {
+            return Err(format!("FMSPC mismatch: expect {}", self.fmspc));
+        }
+
+        let pce_id = decode_hex(&self.pce_id).map_err(|err| format!("decode pceId: {err}"))?;
+        if pce_id.as_slice() != pck.pce_id.as_ref() {
+            return Err(format!("PCE-ID mismatch: expect {}", self.pce_id));
+        }
+
+        for (i, v) in self.tcb_levels.iter().enumerate() {
+            let ok = v
+                .tcb
+                .is_covered_by(&pck.tcb)
+                .map_err(|err| format!("check TCB level #{i}: {err}"))?;
+            if ok {
+                return Ok(v);
+            }
+        }
+
+        Err("no TCB level matches the PCK certificate".to_string())
+    }
+
+    /// Decodes the signed `tcbInfo` JSON, verifying its signature with the TCB signing certificate
+    /// in `signing_chain`, and the chain itself against `root`.
+    pub fn decode_and_verify(
+        b: &[u8],
+        signing_chain: &[u8],
+        root: &X509Ref,
+        crls: &[X509Crl],
+    ) -> Result<Self, String> {
+        let signed: SignedTcbInfo =
+            serde_json::from_slice(b).map_err(|err| format!("decode JSON: {err}"))?;
+
+        verify_signed_json(signed.body, &signed.signature, signing_chain, root, crls)?;
+
+        let out: Self =
+            serde_json::from_str(signed.body.get()).map_err(|err| format!("decode body: {err}"))?;
+        verify_not_expired(&out.next_update)?;
+
+        Ok(out)
+    }
+}
+
+impl Display for TcbStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let v = match self {
+            Self::UpToDate => "UpToDate",
+            Self::SWHardeningNeeded => "SWHardeningNeeded",
+            Self::ConfigurationNeeded => "ConfigurationNeeded",
+            Self::ConfigurationAndSWHardeningNeeded => "ConfigurationAndSWHardeningNeeded",
+            Self::OutOfDate => "OutOfDate",
+            Self::OutOfDateConfigurationNeeded => "OutOfDateConfigurationNeeded",
+            Self::Revoked => "Revoked",
+        };
+
+        write!(f, "{v}")
+    }
+}
+
+/// Decodes a hex-encoded little-endian u32, which is how Intel PCS encodes MISCSELECT.
+fn decode_hex_u32(s: &str) -> Result<u32, String> {
+    let b = decode_hex(s)?;
+    let b: [u8; 4] = b
+        .try_into()
+        .map_err(|v: Vec<u8>| format!("bad length: expect 4, got {}", v.len()))?;
+
+    Ok(u32::from_le_bytes(b))
+}
+
+fn verify_not_expired(next_update: &str) -> Result<(), String> {
+    // PCS dates look like '2023-02-15T10:02:59Z', while ASN.1 wants '20230215100259Z'.
+    let v: String = next_update
+        .chars()
+        .filter(|c| !matches!(c, '-' | ':' | 'T'))
+        .collect();
+    let v = Asn1Time::from_str(&v).map_err(|err| format!("parse nextUpdate: {err}"))?;
+
+    let now = Asn1Time::days_from_now(0).map_err(|err| format!("get current time: {err}"))?;
+    if v < now {
+        return Err(format!("expired at {}, checked at {}", &*v, &*now));
+    }
+
+    Ok(())
+}
+
+fn verify_signed_json(
+    body: &RawValue,
+    signature: &str,
+    signing_chain: &[u8],
+    root: &X509Ref,
+    crls: &[X509Crl],
+) -> Result<(), String> {
+    let chain = x509::decode_pem_certs(signing_chain)
+        .map_err(|err| format!("decode signing chain: {err}"))?;
+    x509::verify_cert_chain(&chain, root, crls)
+        .map_err(|err| format!("verify signing chain: {err}"))?;
+
+    let key = chain[0]
+        .public_key()
+        .and_then(|v| v.ec_key())
+        .map_err(|err| format!("load signing key: {err}"))?;
+    let sig = decode_hex(signature).map_err(|err| format!("decode signature: {err}"))?;
+
+    verify_ecdsa_p256_sha256(&key, &sig, body.get().as_bytes())
+        .map_err(|err| format!("verify signature: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn new_tcb_info(id: Option<&str>, version: u32, svn: u8, status: TcbStatus) -> TcbInfo {
+        let level = json!({
+            "tcb": {
+                "sgxtcbcomponents": vec![json!({ "svn": svn }); 16],
+                "pcesvn": 0,
+            },
+            "tcbStatus": format!("{status}"),
+            "advisoryIDs": ["INTEL-SA-00001"],
+        });
+        let v = json!({
+            "id": id,
+            "version": version,
+            "nextUpdate": "2099-01-01T00:00:00Z",
+            "fmspc": "000000000000",
+            "pceId": "0000",
+            "tcbLevels": [level],
+        });
+
+        serde_json::from_value(v).unwrap()
+    }
+
+    fn new_qe_identity(status: TcbStatus) -> EnclaveIdentity {
+        let v = json!({
+            "id": "QE",
+            "nextUpdate": "2099-01-01T00:00:00Z",
+            "miscselect": "00000000",
+            "miscselectMask": "00000000",
+            "attributes": "00".repeat(16),
+            "attributesMask": "00".repeat(16),
+            "mrsigner": "00".repeat(32),
+            "isvprodid": 0,
+            "tcbLevels": [{
+                "tcb": { "isvsvn": 0 },
+                "tcbStatus": format!("{status}"),
+                "advisoryIDs": ["INTEL-SA-00002"],
+            }],
+        });
+
+        serde_json::from_value(v).unwrap()
+    }
+
+    fn evaluate(tcb_info: &TcbInfo, tee_type: u32, qe: TcbStatus) -> Result<TcbEvaluation, String> {
+        let qe_identity = new_qe_identity(qe);
+        TcbEvaluation::new(
+            tcb_info,
+            tee_type,
+            &SgxExtensions::default(),
+            &qe_identity,
+            &ReportBody::default(),
+        )
+    }
+
+    #[test]
+    fn converge_statuses() {
+        use TcbStatus::*;
+
+        let table = [
+            (UpToDate, UpToDate, UpToDate),
+            (UpToDate, SWHardeningNeeded, SWHardeningNeeded),
+            (UpToDate, ConfigurationNeeded, ConfigurationNeeded),
+            (UpToDate, OutOfDate, OutOfDate),
+            (UpToDate, Revoked, Revoked),
+            (OutOfDate, UpToDate, OutOfDate),
+            (OutOfDate, SWHardeningNeeded, OutOfDate),
+            (OutOfDate, ConfigurationNeeded, OutOfDateConfigurationNeeded),
+            (OutOfDate, OutOfDate, OutOfDate),
+            (OutOfDate, Revoked, Revoked),
+            (Revoked, UpToDate, Revoked),
+            (Revoked, SWHardeningNeeded, Revoked),
+            (Revoked, ConfigurationNeeded, Revoked),
+            (Revoked, OutOfDate, Revoked),
+        ];
+
+        for (qe, platform, expect) in table {
+            let tcb_info = new_tcb_info(Some("SGX"), 3, 0, platform);
+            let got = evaluate(&tcb_info, TEE_TYPE_SGX, qe).unwrap();
+            assert_eq!(got.status, expect, "QE {qe}, platform {platform}");
+            assert_eq!(got.advisory_ids, ["INTEL-SA-00001", "INTEL-SA-00002"]);
+        }
+    }
+
+    #[test]
+    fn no_matching_level() {
+        let tcb_info = new_tcb_info(Some("SGX"), 3, 1, TcbStatus::UpToDate);
+
+        let err = evaluate(&tcb_info, TEE_TYPE_SGX, TcbStatus::UpToDate).unwrap_err();
+        assert_eq!(
+            err,
+            "evaluate platform TCB: no TCB level matches the PCK certificate"
+        );
+
+        let tcb_info = new_tcb_info(Some("SGX"), 3, 0, TcbStatus::UpToDate);
+        let mut qe_identity = new_qe_identity(TcbStatus::UpToDate);
+        qe_identity.tcb_levels[0].tcb.isvsvn = 1;
+        let err = TcbEvaluation::new(
+            &tcb_info,
+            TEE_TYPE_SGX,
+            &SgxExtensions::default(),
+            &qe_identity,
+            &ReportBody::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err, "evaluate QE identity: no TCB level matches isv_svn 0");
+    }
+
+    #[test]
+    fn tee_type_mismatch() {
+        let table = [
+            (Some("SGX"), 3, TEE_TYPE_SGX, true),
+            (Some("TDX"), 3, TEE_TYPE_TDX, true),
+            (None, 2, TEE_TYPE_SGX, true),
+            (Some("TDX"), 3, TEE_TYPE_SGX, false),
+            (Some("SGX"), 3, TEE_TYPE_TDX, false),
+            (None, 2, TEE_TYPE_TDX, false),
+            (None, 3, TEE_TYPE_SGX, false),
+        ];
+
+        for (id, version, tee_type, ok) in table {
+            let tcb_info = new_tcb_info(id, version, 0, TcbStatus::UpToDate);
+            let got = evaluate(&tcb_info, tee_type, TcbStatus::UpToDate);
+            assert_eq!(
+                got.is_ok(),
+                ok,
+                "id {id:?}, tee_type {tee_type:#x}: {got:?}"
+            );
+        }
+    }
+}
@@ -1,4 +1,72 @@
+use crate::sgx::{Attributes, MiscSelect};
+
+/// Format of the dumped output. The text format is meant for humans, while the others are
+/// machine-readable with byte arrays encoded in hex.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    /// Fields keep the same order as the other formats, except plain values go ahead of tables in
+    /// each table as TOML requires.
+    Toml,
+}
+
+/// Encoding of binary inputs such as quotes and SIGSTRUCTs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum InputEncoding {
+    /// Detect the encoding among the others.
+    #[default]
+    Auto,
+    Raw,
+    /// Hex with or without the '0x' prefix, where whitespaces are ignored.
+    Hex,
+    Base64,
+    /// URL-safe base64.
+    Base64url,
+    /// Base64 armored by '-----BEGIN ...-----' and '-----END ...-----'.
+    Pem,
+}
+
 pub enum KeyFormat {
     DER,
     PEM,
 }
+
+/// Source which a TARGETINFO is built from.
+pub enum TargetInfoSource {
+    SigStruct,
+    /// Either an EPID-based or a SGX DCAP-based quote.
+    Quote,
+}
+
+/// Collateral used to verify a quote beyond its own signatures.
+#[derive(Default)]
+pub struct QuoteCollateral {
+    /// PEM-encoded Intel SGX Root CA certificate.
+    pub root_ca: Option<Vec<u8>>,
+    /// CRLs issued by the root CA and the PCK CA, in PEM or DER.
+    pub crls: Vec<Vec<u8>>,
+    /// Signed `tcbInfo` JSON from Intel PCS.
+    pub tcb_info: Option<Vec<u8>>,
+    /// Signed `enclaveIdentity` JSON of the QE from Intel PCS.
+    pub qe_identity: Option<Vec<u8>>,
+    /// PEM-encoded chain of the TCB signing certificate, which signs `tcb_info` and `qe_identity`.
+    pub tcb_signing_chain: Option<Vec<u8>>,
+}
+
+/// Properties of an enclave which are signed into a SIGSTRUCT.
+pub struct EnclaveSigningParams {
+    pub mr_enclave: [u8; 32],
+    pub attributes: Attributes,
+    pub attribute_mask: Attributes,
+    pub misc_select: MiscSelect,
+    pub misc_mask: MiscSelect,
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    /// Marks the SIGSTRUCT as of a debug enclave, which also sets the DEBUG attribute.
+    pub debug: bool,
+    /// Signing date as (year, month, day), which defaults to today in UTC.
+    pub date: Option<(u16, u8, u8)>,
+}
@@ -0,0 +1,484 @@
+use std::io::Write;
+
+use encoding::hex;
+use openssl::pkey::PKey;
+use openssl::sha;
+use openssl::sign::Signer;
+use openssl::symm::Cipher;
+use openssl::x509::{X509Crl, X509};
+
+use crate::app::ecdsa::{ecdsa_p256_pubkey_from_raw, verify_ecdsa_p256_sha256};
+use crate::app::ias::IasReport;
+use crate::app::policy::Policy;
+use crate::app::rsa;
+use crate::app::tcb::{EnclaveIdentity, TcbEvaluation, TcbInfo};
+use crate::app::types::QuoteCollateral;
+use crate::app::x509;
+use crate::sgx::{Quote3, QuoteBody, Report, SgxExtensions, SigStruct};
+use crate::sgx::{SIG_STRUCT_HEADER, SIG_STRUCT_HEADER2};
+
+/// Verifies the signatures of a DCAP-based quote offline, i.e. the ISV enclave report signature,
+/// the binding between the attestation key and the QE report, and the QE report signature made
+/// by the PCK certificate embedded in the certification data.
+///
+/// The PCK certificate chain is validated as well if `collateral` provides the root CA, and the
+/// TCB status is evaluated if it also provides the TCB info and QE identity.
+pub fn verify_quote3<W>(out: &mut W, b: &[u8], collateral: &QuoteCollateral) -> Result<(), String>
+where
+    W: Write,
+{
+    let quote = Quote3::try_from(b).map_err(|err| format!("parse: {err}"))?;
+
+    let mut checks = vec![
+        (
+            "isv_enclave_report_sig",
+            verify_isv_enclave_report_sig(&quote),
+        ),
+        ("qe_report_data", verify_qe_report_data(&quote)),
+        ("qe_report_sig", verify_qe_report_sig(&quote)),
+    ];
+
+    let mut evaluation = None;
+    if collateral.root_ca.is_some() {
+        match decode_root_ca_and_crls(collateral) {
+            Ok((root, crls)) => {
+                checks.push((
+                    "pck_cert_chain",
+                    verify_pck_cert_chain(&quote, &root, &crls),
+                ));
+
+                if collateral.tcb_info.is_some() {
+                    match evaluate_tcb(&quote, collateral, &root, &crls) {
+                        Ok(v) => {
+                            checks.push(("tcb_evaluation", Ok(())));
+                            evaluation = Some(v);
+                        }
+                        Err(err) => checks.push(("tcb_evaluation", Err(err))),
+                    }
+                }
+            }
+            Err(err) => checks.push(("collateral", Err(err))),
+        }
+    }
+
+    let failures = report_checks(out, &checks)?;
+
+    if let Some(v) = evaluation {
+        writeln!(out, "{v}").map_err(|err| format!("write: {err}"))?;
+    }
+
+    if failures != 0 {
+        return Err(format!("{failures} check(s) failed"));
+    }
+
+    Ok(())
+}
+
+/// Appraises the report body of a SGX DCAP-based quote against the TOML-encoded `policy`, and
+/// reports the outcome of each rule by name. Signatures of the quote aren't verified here.
+pub fn appraise_quote<W>(out: &mut W, b: &[u8], policy: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let quote = Quote3::try_from(b).map_err(|err| format!("parse: {err}"))?;
+    let body = match &quote.body {
+        QuoteBody::Sgx(v) => v,
+        _ => return Err("quote body isn't an SGX report body".to_string()),
+    };
+
+    let policy = std::str::from_utf8(policy).map_err(|err| format!("decode policy: {err}"))?;
+    let policy: Policy = toml::from_str(policy).map_err(|err| format!("parse policy: {err}"))?;
+
+    let failures = report_checks(out, &policy.appraise(body))?;
+
+    if failures != 0 {
+        return Err(format!("{failures} rule(s) failed"));
+    }
+
+    Ok(())
+}
+
+/// Verifies an IAS attestation verification report offline, i.e. the report signature made by the
+/// report signing certificate, which is validated against the locally supplied IAS report signing
+/// CA, and dumps the report along with the ISV enclave quote body.
+pub fn verify_ias_report<W>(
+    out: &mut W,
+    report: &[u8],
+    signature: &[u8],
+    signing_chain: &[u8],
+    ca: &[u8],
+) -> Result<(), String>
+where
+    W: Write,
+{
+    let ias_report: IasReport =
+        serde_json::from_slice(report).map_err(|err| format!("parse report: {err}"))?;
+    let quote = ias_report
+        .isv_enclave_quote()
+        .map_err(|err| format!("parse isvEnclaveQuoteBody: {err}"))?;
+
+    let result = X509::from_pem(ca)
+        .map_err(|err| format!("decode CA: {err}"))
+        .and_then(|ca| IasReport::verify_signature(report, signature, signing_chain, &ca));
+
+    let write_err = |err: std::io::Error| format!("write: {err}");
+    match &result {
+        Ok(_) => writeln!(out, "report_signature = ok"),
+        Err(err) => writeln!(out, "report_signature = failed: {err}"),
+    }
+    .map_err(write_err)?;
+    writeln!(out, "{ias_report}").map_err(write_err)?;
+    writeln!(out).map_err(write_err)?;
+
+    writeln!(out, "[isv_enclave_quote.header]").map_err(write_err)?;
+    writeln!(out, "{}", quote.header).map_err(write_err)?;
+    writeln!(out).map_err(write_err)?;
+    writeln!(out, "[isv_enclave_quote.body]").map_err(write_err)?;
+    writeln!(out, "{}", quote.body).map_err(write_err)?;
+
+    result.map_err(|_| "1 check(s) failed".to_string())
+}
+
+/// Verifies a SIGSTRUCT offline, i.e. the fixed header values, the RSA-3072 key with exponent 3,
+/// the RSASSA-PKCS1-v1_5 signature over the header and body, and q1/q2 derived from the
+/// signature and modulus, which are all required by EINIT.
+///
+/// ref: Intel SDM Vol. 3D, Section 38.13 "Enclave Signature Structure (SIGSTRUCT)"
+pub fn verify_sig_struct<W>(out: &mut W, b: &[u8]) -> Result<(), String>
+where
+    W: Write,
+{
+    let ss = SigStruct::try_from(b).map_err(|err| format!("parse: {err}"))?;
+
+    let mut checks = vec![
+        ("header", verify_sig_struct_header(&ss)),
+        ("key", verify_sig_struct_key(&ss)),
+        ("signature", verify_sig_struct_signature(&ss)),
+    ];
+    match verify_sig_struct_q1_q2(&ss) {
+        Ok((q1, q2)) => {
+            checks.push(("q1", q1));
+            checks.push(("q2", q2));
+        }
+        Err(err) => checks.push(("q1_q2", Err(err))),
+    }
+
+    let failures = report_checks(out, &checks)?;
+
+    if failures != 0 {
+        return Err(format!("{failures} check(s) failed"));
+    }
+
+    Ok(())
+}
+
+/// Writes out the outcome of each named check as "name = ok" or "name = failed: reason", and
+/// returns the number of failed ones.
+fn report_checks<W>(out: &mut W, checks: &[(&str, Result<(), String>)]) -> Result<usize, String>
+where
+    W: Write,
+{
+    let mut failures = 0;
+    for (name, result) in checks {
+        match result {
+            Ok(_) => writeln!(out, "{name} = ok"),
+            Err(err) => {
+                failures += 1;
+                writeln!(out, "{name} = failed: {err}")
+            }
+        }
+        .map_err(|err| format!("write: {err}"))?;
+    }
+
+    Ok(failures)
+}
+
+/// Verifies the MAC of a REPORT, i.e. the AES-128-CMAC over the report body with `report_key`.
+pub fn verify_report_mac(report: &Report, report_key: &[u8]) -> Result<(), String> {
+    if report_key.len() != 16 {
+        let hint = format!("bad report key length: expect 16, got {}", report_key.len());
+        return Err(hint);
+    }
+
+    let key = PKey::cmac(&Cipher::aes_128_cbc(), report_key)
+        .map_err(|err| format!("load report key: {err}"))?;
+    let mac = Signer::new_without_digest(&key)
+        .and_then(|mut v| {
+            v.update(report.body.as_ref())?;
+            v.sign_to_vec()
+        })
+        .map_err(|err| format!("calculate CMAC: {err}"))?;
+
+    if mac != report.mac {
+        return Err("MAC mismatch".to_string());
+    }
+
+    Ok(())
+}
+
+fn decode_root_ca_and_crls(collateral: &QuoteCollateral) -> Result<(X509, Vec<X509Crl>), String> {
+    let root = match &collateral.root_ca {
+        Some(v) => X509::from_pem(v).map_err(|err| format!("decode root CA: {err}"))?,
+        None => return Err("missing root CA".to_string()),
+    };
+
+    let crls = collateral
+        .crls
+        .iter()
+        .enumerate()
+        .map(|(i, v)| x509::decode_crl(v).map_err(|err| format!("decode CRL #{i}: {err}")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((root, crls))
+}
+
+fn evaluate_tcb(
+    quote: &Quote3,
+    collateral: &QuoteCollateral,
+    root: &X509,
+    crls: &[X509Crl],
+) -> Result<TcbEvaluation, String> {
+    let (tcb_info, qe_identity, chain) = match (
+        &collateral.tcb_info,
+        &collateral.qe_identity,
+        &collateral.tcb_signing_chain,
+    ) {
+        (Some(a), Some(b), Some(c)) => (a, b, c),
+        _ => return Err("tcbInfo, QE identity and TCB signing chain are all required".to_string()),
+    };
+
+    let tcb_info = TcbInfo::decode_and_verify(tcb_info, chain, root, crls)
+        .map_err(|err| format!("verify tcbInfo: {err}"))?;
+    let qe_identity = EnclaveIdentity::decode_and_verify(qe_identity, chain, root, crls)
+        .map_err(|err| format!("verify QE identity: {err}"))?;
+
+    let leaf = pck_certs(quote)?.swap_remove(0);
+    let der = leaf
+        .to_der()
+        .map_err(|err| format!("encode PCK cert: {err}"))?;
+    let pck = SgxExtensions::from_cert_der(&der)
+        .map_err(|err| format!("parse PCK cert SGX extensions: {err}"))?;
+
+    let tee_type = quote.header.att_key_data_0;
+    TcbEvaluation::new(
+        &tcb_info,
+        tee_type,
+        &pck,
+        &qe_identity,
+        &quote.signature.qe_report,
+    )
+}
+
+fn verify_isv_enclave_report_sig(quote: &Quote3) -> Result<(), String> {
+    let key = ecdsa_p256_pubkey_from_raw(&quote.signature.attest_pub_key)
+        .map_err(|err| format!("load attestation key: {err}"))?;
+
+    let mut msg = quote.header.as_ref().to_vec();
+    if let Some(v) = &quote.body_descriptor {
+        msg.extend_from_slice(v.as_ref());
+    }
+    msg.extend_from_slice(quote.body.as_ref());
+
+    verify_ecdsa_p256_sha256(&key, &quote.signature.sig, &msg)
+}
+
+fn verify_qe_report_data(quote: &Quote3) -> Result<(), String> {
+    let sig = &quote.signature;
+
+    let mut h = sha::Sha256::new();
+    h.update(&sig.attest_pub_key);
+    h.update(&sig.qe_auth_data.data);
+    let expected = h.finish();
+
+    let report_data = sig.qe_report.report_data;
+    if report_data[..32] != expected || report_data[32..].iter().any(|v| *v != 0) {
+        return Err(
+            "report_data isn't SHA-256(attest_pub_key || qe_auth_data) padded with zeros"
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+fn pck_certs(quote: &Quote3) -> Result<Vec<X509>, String> {
+    let pem = quote
+        .signature
+        .certification_data
+        .pck_certs()
+        .ok_or_else(|| "no PCK certificate in certification data".to_string())?;
+
+    let certs = x509::decode_pem_certs(pem).map_err(|err| format!("decode PCK certs: {err}"))?;
+    if certs.is_empty() {
+        return Err("empty PCK certificate chain".to_string());
+    }
+
+    Ok(certs)
+}
+
+fn verify_pck_cert_chain(quote: &Quote3, root: &X509, crls: &[X509Crl]) -> Result<(), String> {
+    let certs = pck_certs(quote)?;
+
+    x509::verify_cert_chain(&certs, root, crls)
+}
+
+fn verify_qe_report_sig(quote: &Quote3) -> Result<(), String> {
+    let sig = &quote.signature;
+
+    let certs = pck_certs(quote)?;
+    let leaf = &certs[0];
+
+    let key = leaf
+        .public_key()
+        .and_then(|v| v.ec_key())
+        .map_err(|err| format!("load PCK public key: {err}"))?;
+
+    verify_ecdsa_p256_sha256(&key, &sig.qe_report_sig, sig.qe_report.as_ref())
+}
+
+fn verify_sig_struct_header(ss: &SigStruct) -> Result<(), String> {
+    let (header, header2) = (ss.header.header, ss.header.header2);
+    if header != SIG_STRUCT_HEADER {
+        return Err(format!(
+            "bad header: {}",
+            hex::encode_to_string(header.as_ref())
+        ));
+    }
+    if header2 != SIG_STRUCT_HEADER2 {
+        return Err(format!(
+            "bad header2: {}",
+            hex::encode_to_string(header2.as_ref())
+        ));
+    }
+
+    Ok(())
+}
+
+fn verify_sig_struct_key(ss: &SigStruct) -> Result<(), String> {
+    let exponent = u32::from_le_bytes(ss.key.exponent);
+    if exponent != 3 {
+        return Err(format!("bad exponent: expect 3, got {exponent}"));
+    }
+
+    let modulus = rsa::bn_from_le(&ss.key.modulus).map_err(|err| format!("modulus: {err}"))?;
+    let bits = modulus.num_bits();
+    if bits != 3072 {
+        return Err(format!("bad modulus: expect 3072 bits, got {bits}"));
+    }
+
+    Ok(())
+}
+
+fn verify_sig_struct_signature(ss: &SigStruct) -> Result<(), String> {
+    let key = rsa::rsa_pubkey_from_le(&ss.key.modulus, &ss.key.exponent)
+        .map_err(|err| format!("load key: {err}"))?;
+
+    rsa::verify_rsa_sha256(&key, &ss.key.signature, &ss.signed_message())
+}
+
+type CheckResult = Result<(), String>;
+
+/// Checks q1 and q2 in the buffer against those computed from the signature and modulus.
+fn verify_sig_struct_q1_q2(ss: &SigStruct) -> Result<(CheckResult, CheckResult), String> {
+    let signature =
+        rsa::bn_from_le(&ss.key.signature).map_err(|err| format!("signature: {err}"))?;
+    let modulus = rsa::bn_from_le(&ss.key.modulus).map_err(|err| format!("modulus: {err}"))?;
+    if modulus.num_bits() == 0 {
+        return Err("zero modulus".to_string());
+    }
+
+    let (q1, q2) = rsa::compute_q1_q2(&signature, &modulus)?;
+
+    let check = |got: [u8; rsa::LENGTH_RSA3072], expected: [u8; rsa::LENGTH_RSA3072]| {
+        if got == expected {
+            Ok(())
+        } else {
+            Err("inconsistent with signature and modulus".to_string())
+        }
+    };
+    let buffer = ss.buffer;
+
+    Ok((check(buffer.q1, q1), check(buffer.q2, q2)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTE: &[u8] = include_bytes!("../../testdata/quote.bin");
+    const SIG_STRUCT: &[u8] = include_bytes!("../../testdata/enclave.sig");
+
+    /// Offset of the ISV enclave report body, right after the 48-byte quote header.
+    const OFFSET_REPORT_BODY: usize = 48;
+
+    #[test]
+    fn quote3_signatures() {
+        let mut out = vec![];
+        verify_quote3(&mut out, QUOTE, &QuoteCollateral::default()).unwrap();
+
+        let expect = "isv_enclave_report_sig = ok\nqe_report_data = ok\nqe_report_sig = ok\n";
+        assert_eq!(String::from_utf8(out).unwrap(), expect);
+    }
+
+    #[test]
+    fn quote3_tampered_body() {
+        let mut quote = QUOTE.to_vec();
+        quote[OFFSET_REPORT_BODY + 64] ^= 0x01;
+
+        let mut out = vec![];
+        let err = verify_quote3(&mut out, &quote, &QuoteCollateral::default()).unwrap_err();
+        assert_eq!(err, "1 check(s) failed");
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.starts_with("isv_enclave_report_sig = failed: "),
+            "{out}"
+        );
+        assert!(
+            out.contains("\nqe_report_data = ok\nqe_report_sig = ok\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn appraise_quote_rules() {
+        let policy = br#"
+mr_enclave = ["7f71691ecfae8db7001cc8b159241857e17476c78b3b9420064625b8df6954c8"]
+isv_prod_id = 0
+forbidden_attributes = 0x02
+"#;
+
+        let mut out = vec![];
+        let err = appraise_quote(&mut out, QUOTE, policy).unwrap_err();
+        assert_eq!(err, "1 rule(s) failed");
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("mr_enclave = ok\n"), "{out}");
+        assert!(out.contains("isv_prod_id = ok\n"), "{out}");
+        assert!(out.contains("forbidden_attributes = failed: "), "{out}");
+    }
+
+    #[test]
+    fn sig_struct() {
+        let mut out = vec![];
+        verify_sig_struct(&mut out, SIG_STRUCT).unwrap();
+
+        let expect = "header = ok\nkey = ok\nsignature = ok\nq1 = ok\nq2 = ok\n";
+        assert_eq!(String::from_utf8(out).unwrap(), expect);
+    }
+
+    #[test]
+    fn sig_struct_tampered_body() {
+        // Flips a bit of ENCLAVEHASH, which is at offset 960.
+        let mut ss = SIG_STRUCT.to_vec();
+        ss[960] ^= 0x01;
+
+        let mut out = vec![];
+        let err = verify_sig_struct(&mut out, &ss).unwrap_err();
+        assert_eq!(err, "1 check(s) failed");
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\nsignature = failed: "), "{out}");
+        assert!(out.ends_with("\nq1 = ok\nq2 = ok\n"), "{out}");
+    }
+}
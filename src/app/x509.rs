@@ -0,0 +1,307 @@
+use openssl::asn1::{Asn1Time, Asn1TimeRef};
+use openssl::nid::Nid;
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::verify::X509VerifyFlags;
+use openssl::x509::{CrlStatus, X509Crl, X509Ref, X509StoreContext, X509VerifyResult, X509};
+
+// Values of X509_V_ERR_* from OpenSSL's x509_vfy.h, which the openssl crate has no constants for.
+const X509_V_ERR_CERT_NOT_YET_VALID: i32 = 9;
+const X509_V_ERR_CERT_HAS_EXPIRED: i32 = 10;
+
+/// Decodes concatenated PEM certificates. Unlike `X509::stack_from_pem`, it tolerates a missing
+/// line break between two certificates, which is common in the PCK certificate chain of quotes.
+pub fn decode_pem_certs(pem: &[u8]) -> Result<Vec<X509>, String> {
+    const END: &[u8] = b"-----END CERTIFICATE-----";
+
+    let mut out = vec![];
+    let mut v = pem;
+    while let Some(i) = v.windows(END.len()).position(|w| w == END) {
+        let (cert, rest) = v.split_at(i + END.len());
+        let cert = X509::from_pem(cert).map_err(|err| format!("decode #{}: {err}", out.len()))?;
+        out.push(cert);
+        v = rest;
+    }
+
+    Ok(out)
+}
+
+/// Decodes a CRL in either PEM or DER.
+pub fn decode_crl(b: &[u8]) -> Result<X509Crl, String> {
+    if b.starts_with(b"-----BEGIN") {
+        X509Crl::from_pem(b).map_err(|err| format!("decode PEM: {err}"))
+    } else {
+        X509Crl::from_der(b).map_err(|err| format!("decode DER: {err}"))
+    }
+}
+
+/// Validates `chain`, which starts with the leaf certificate, against the trusted `root`.
+///
+/// A copy of the root CA certificate at the end of `chain` is allowed only if it's identical to
+/// `root`. The chain is built and checked by OpenSSL, which covers the current time, signatures
+/// and the basic constraints of every issuer, i.e. CA:TRUE and pathLen. Then each non-root
+/// certificate is looked up in the CRL issued by its issuer, if any is found in `crls`.
+pub fn verify_cert_chain(chain: &[X509], root: &X509Ref, crls: &[X509Crl]) -> Result<(), String> {
+    let root_der = root
+        .to_der()
+        .map_err(|err| format!("encode root CA: {err}"))?;
+
+    let mut certs: Vec<&X509Ref> = chain.iter().map(|v| v.as_ref()).collect();
+    if let Some(last) = certs.last() {
+        if last.issued(last) == X509VerifyResult::OK {
+            let der = last
+                .to_der()
+                .map_err(|err| format!("encode chain root: {err}"))?;
+            if der != root_der {
+                let hint = format!(
+                    "root CA '{}' in chain differs from the supplied one",
+                    common_name(last)
+                );
+                return Err(hint);
+            }
+            certs.pop();
+        }
+    }
+    if certs.is_empty() {
+        return Err("empty certificate chain".to_string());
+    }
+
+    let now = Asn1Time::days_from_now(0).map_err(|err| format!("get current time: {err}"))?;
+
+    verify_path(&certs, root, &now)?;
+
+    certs.push(root);
+    for (i, pair) in certs.windows(2).enumerate() {
+        verify_not_revoked(pair[0], pair[1], crls, &now)
+            .map_err(|err| format!("cert #{i}: {err}"))?;
+    }
+
+    Ok(())
+}
+
+pub fn common_name(cert: &X509Ref) -> String {
+    cert.subject_name()
+        .entries_by_nid(Nid::COMMONNAME)
+        .next()
+        .map(|v| String::from_utf8_lossy(v.data().as_slice()).into_owned())
+        .unwrap_or_default()
+}
+
+/// Verifies the path from `certs[0]` through the rest of `certs` up to the trusted `root`. `now`
+/// is only used to tell when the check happened in errors about the validity period.
+fn verify_path(certs: &[&X509Ref], root: &X509Ref, now: &Asn1TimeRef) -> Result<(), String> {
+    let mut store = X509StoreBuilder::new().map_err(|err| format!("new X509 store: {err}"))?;
+    store
+        .add_cert(root.to_owned())
+        .map_err(|err| format!("add root CA: {err}"))?;
+    // OpenSSL skips the signature of the self-signed trust anchor unless asked to.
+    store
+        .set_flags(X509VerifyFlags::CHECK_SS_SIGNATURE)
+        .map_err(|err| format!("set verify flags: {err}"))?;
+    let store = store.build();
+
+    let mut untrusted = Stack::new().map_err(|err| format!("new X509 stack: {err}"))?;
+    for v in &certs[1..] {
+        untrusted
+            .push((*v).to_owned())
+            .map_err(|err| format!("push intermediate CA: {err}"))?;
+    }
+
+    let mut ctx = X509StoreContext::new().map_err(|err| format!("new X509 context: {err}"))?;
+    ctx.init(&store, certs[0], &untrusted, |c| {
+        if c.verify_cert()? {
+            return Ok(Ok(()));
+        }
+
+        let cert = c.current_cert();
+        let reason = match (c.error().as_raw(), cert) {
+            (X509_V_ERR_CERT_NOT_YET_VALID, Some(v)) => {
+                format!("not valid until {}, checked at {now}", v.not_before())
+            }
+            (X509_V_ERR_CERT_HAS_EXPIRED, Some(v)) => {
+                format!("expired at {}, checked at {now}", v.not_after())
+            }
+            _ => c.error().error_string().to_string(),
+        };
+
+        let hint = format!(
+            "cert #{} '{}': {reason}",
+            c.error_depth(),
+            cert.map(common_name).unwrap_or_default(),
+        );
+        Ok(Err(hint))
+    })
+    .map_err(|err| format!("verify chain: {err}"))?
+}
+
+fn verify_not_revoked(
+    cert: &X509Ref,
+    issuer: &X509Ref,
+    crls: &[X509Crl],
+    now: &Asn1TimeRef,
+) -> Result<(), String> {
+    let issuer_name = issuer.subject_name();
+    let crl = crls.iter().find(|v| {
+        v.issuer_name()
+            .try_cmp(issuer_name)
+            .map(|v| v.is_eq())
+            .unwrap_or(false)
+    });
+    let crl = match crl {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+
+    let key = issuer
+        .public_key()
+        .map_err(|err| format!("load CRL issuer public key: {err}"))?;
+    match crl.verify(&key) {
+        Ok(true) => {}
+        Ok(false) => {
+            let hint = format!("CRL of '{}' has a bad signature", common_name(issuer));
+            return Err(hint);
+        }
+        Err(err) => return Err(format!("verify CRL signature: {err}")),
+    }
+
+    if let Some(next_update) = crl.next_update() {
+        if next_update < now {
+            let hint = format!(
+                "CRL of '{}' expired at {next_update}, checked at {now}",
+                common_name(issuer)
+            );
+            return Err(hint);
+        }
+    }
+
+    // `get_by_cert` only takes an owned certificate.
+    let cert = cert.to_owned();
+    match crl.get_by_cert(&cert) {
+        CrlStatus::Revoked(v) => Err(format!(
+            "'{}' revoked at {}, checked at {now}",
+            common_name(&cert),
+            v.revocation_date()
+        )),
+        CrlStatus::NotRevoked | CrlStatus::RemoveFromCrl(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::{PKey, Private};
+    use openssl::x509::extension::BasicConstraints;
+    use openssl::x509::{X509Builder, X509NameBuilder, X509};
+
+    use super::*;
+
+    fn new_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn new_cert(
+        cn: &str,
+        key: &PKey<Private>,
+        issuer: Option<(&X509, &PKey<Private>)>,
+        not_before: &Asn1Time,
+        not_after: &Asn1Time,
+    ) -> X509 {
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+        let name = name.build();
+
+        let mut b = X509Builder::new().unwrap();
+        b.set_version(2).unwrap();
+        let serial = BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap();
+        b.set_serial_number(&serial).unwrap();
+        b.set_subject_name(&name).unwrap();
+        b.set_pubkey(key).unwrap();
+        b.set_not_before(not_before).unwrap();
+        b.set_not_after(not_after).unwrap();
+        let (issuer_name, signing_key) = match issuer {
+            Some((cert, key)) => (cert.subject_name(), key),
+            None => {
+                let ca = BasicConstraints::new().critical().ca().build().unwrap();
+                b.append_extension(ca).unwrap();
+                (name.as_ref(), key)
+            }
+        };
+        b.set_issuer_name(issuer_name).unwrap();
+        b.sign(signing_key, MessageDigest::sha256()).unwrap();
+
+        b.build()
+    }
+
+    #[test]
+    fn expired_leaf() {
+        let (root_key, leaf_key) = (new_key(), new_key());
+        let root = new_cert(
+            "Root",
+            &root_key,
+            None,
+            &Asn1Time::from_unix(1_000_000_000).unwrap(),
+            &Asn1Time::days_from_now(365).unwrap(),
+        );
+        // Expired at 2004-11-09 11:33:20 UTC.
+        let leaf = new_cert(
+            "Leaf",
+            &leaf_key,
+            Some((&root, &root_key)),
+            &Asn1Time::from_unix(1_000_000_000).unwrap(),
+            &Asn1Time::from_unix(1_100_000_000).unwrap(),
+        );
+
+        let err = verify_cert_chain(&[leaf], &root, &[]).unwrap_err();
+        assert!(
+            err.starts_with("cert #0 'Leaf': expired at Nov  9 11:33:20 2004 GMT, checked at "),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn valid_leaf() {
+        let (root_key, leaf_key) = (new_key(), new_key());
+        let not_before = Asn1Time::from_unix(1_000_000_000).unwrap();
+        let not_after = Asn1Time::days_from_now(365).unwrap();
+        let root = new_cert("Root", &root_key, None, &not_before, &not_after);
+        let leaf = new_cert(
+            "Leaf",
+            &leaf_key,
+            Some((&root, &root_key)),
+            &not_before,
+            &not_after,
+        );
+
+        verify_cert_chain(&[leaf], &root, &[]).unwrap();
+    }
+
+    #[test]
+    fn non_ca_issuer() {
+        let (root_key, leaf_key, sub_key) = (new_key(), new_key(), new_key());
+        let not_before = Asn1Time::from_unix(1_000_000_000).unwrap();
+        let not_after = Asn1Time::days_from_now(365).unwrap();
+        let root = new_cert("Root", &root_key, None, &not_before, &not_after);
+        let leaf = new_cert(
+            "Leaf",
+            &leaf_key,
+            Some((&root, &root_key)),
+            &not_before,
+            &not_after,
+        );
+        let sub = new_cert(
+            "Sub",
+            &sub_key,
+            Some((&leaf, &leaf_key)),
+            &not_before,
+            &not_after,
+        );
+
+        let err = verify_cert_chain(&[sub, leaf], &root, &[]).unwrap_err();
+        assert!(err.starts_with("cert #1 'Leaf': "), "{err}");
+    }
+}
@@ -1,5 +1,7 @@
 use clap::Parser;
 
+use crate::app::types::{InputEncoding, OutputFormat};
+
 /// CLI helps working with gramine libOS.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -8,6 +10,78 @@ pub struct Cli {
     pub cmd: Cmd,
 }
 
+/// Inputs of a binary structure, such as a quote or a SIGSTRUCT.
+#[derive(clap::Args, Debug)]
+pub struct InputArgs {
+    /// Paths to the inputs, which may be '-' for stdin, directories or glob patterns. Each input
+    /// is processed as a separate record headed by its path if more than one is given.
+    #[arg(long = "in", short = 'i', required = true, num_args = 1..)]
+    pub paths: Vec<String>,
+    /// Encoding of the input, which is detected automatically by default.
+    #[arg(long, value_enum, default_value_t)]
+    pub input_encoding: InputEncoding,
+}
+
+/// Local collateral files for verifying quotes.
+#[derive(clap::Args, Debug)]
+pub struct CollateralArgs {
+    /// Path to the PEM-encoded Intel SGX Root CA certificate. The PCK certificate chain is
+    /// validated against it if specified.
+    #[arg(long)]
+    pub root_ca: Option<String>,
+    /// Path to the CRL issued by the Intel SGX Root CA, in PEM or DER.
+    #[arg(long, requires = "root_ca")]
+    pub root_ca_crl: Option<String>,
+    /// Path to the CRL issued by the Intel SGX PCK Platform/Processor CA, in PEM or DER.
+    #[arg(long, requires = "root_ca")]
+    pub pck_crl: Option<String>,
+    /// Path to the signed tcbInfo JSON from Intel PCS. The platform TCB status is evaluated if
+    /// specified.
+    #[arg(long, requires = "qe_identity", requires = "tcb_signing_chain")]
+    pub tcb_info: Option<String>,
+    /// Path to the signed QE identity (enclaveIdentity) JSON from Intel PCS.
+    #[arg(long, requires = "tcb_info")]
+    pub qe_identity: Option<String>,
+    /// Path to the PEM-encoded chain of the Intel SGX TCB Signing certificate.
+    #[arg(long, requires = "root_ca", requires = "tcb_info")]
+    pub tcb_signing_chain: Option<String>,
+}
+
+/// Properties of an enclave to sign into a SIGSTRUCT.
+#[derive(clap::Args, Debug)]
+pub struct EnclaveArgs {
+    /// Hex-encoded MRENCLAVE of the enclave.
+    #[arg(long)]
+    pub mr_enclave: String,
+    /// Flags of ATTRIBUTES, which defaults to MODE64BIT.
+    #[arg(long, default_value = "0x4", value_parser = parse_int::<u64>)]
+    pub attributes: u64,
+    /// XFRM of ATTRIBUTES, which defaults to X87 and SSE.
+    #[arg(long, default_value = "0x3", value_parser = parse_int::<u64>)]
+    pub xfrm: u64,
+    /// Bits of the ATTRIBUTES flags enforced by EINIT.
+    #[arg(long, default_value = "0xffffffffffffffff", value_parser = parse_int::<u64>)]
+    pub attribute_mask: u64,
+    /// Bits of XFRM enforced by EINIT, which leaves out those safe to differ by default.
+    #[arg(long, default_value = "0xfffffffffff9ff1b", value_parser = parse_int::<u64>)]
+    pub xfrm_mask: u64,
+    #[arg(long, default_value = "0", value_parser = parse_int::<u32>)]
+    pub misc_select: u32,
+    /// Bits of MISCSELECT enforced by EINIT.
+    #[arg(long, default_value = "0xffffffff", value_parser = parse_int::<u32>)]
+    pub misc_mask: u32,
+    #[arg(long, default_value_t)]
+    pub isv_prod_id: u16,
+    #[arg(long, default_value_t)]
+    pub isv_svn: u16,
+    /// Sign as a debug enclave, which also sets the DEBUG attribute.
+    #[arg(long)]
+    pub debug: bool,
+    /// Signing date in 'YYYY-MM-DD', which defaults to today in UTC.
+    #[arg(long, value_parser = parse_date)]
+    pub date: Option<(u16, u8, u8)>,
+}
+
 #[derive(clap::Subcommand, Debug)]
 pub enum Cmd {
     /// Generate a private key suitable for signing SGX enclaves.
@@ -18,20 +92,210 @@ pub enum Cmd {
         #[arg(long, short)]
         out: Option<String>,
     },
+    /// Print the MRSIGNER of enclaves signed by a key, i.e. the SHA-256 digest of its
+    /// little-endian modulus.
+    KeyMrsigner {
+        /// Path to the RSA-3072 private key in PEM or DER, such as one generated by 'generate-key'.
+        #[arg(long = "in", short = 'i')]
+        in_path: String,
+    },
     /// Check SGX availability.
     IsSgxAvailable {
         /// Whether print detailed info.
         #[arg(long, short)]
         quite: bool,
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Dump a quote, which is detected as EPID-based or DCAP-based by its version.
+    DumpQuote {
+        #[command(flatten)]
+        input: InputArgs,
     },
     /// Dump a DCAP-based quote.
     DumpQuote3 {
+        #[command(flatten)]
+        input: InputArgs,
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        /// Dotted path of a field to print the raw value of instead of the whole dump, such as
+        /// 'body.mr_enclave'. It can be repeated, with values printed one per line.
+        #[arg(long = "field", conflicts_with = "format")]
+        fields: Vec<String>,
+    },
+    /// Compare two DCAP-based quotes, listing the fields that differ along with a summary.
+    DiffQuote {
+        /// Path to the first quote, which may be '-' for stdin.
+        a: String,
+        /// Path to the second quote, which may be '-' for stdin.
+        b: String,
+        /// Encoding of both quotes, which is detected automatically by default.
+        #[arg(long, value_enum, default_value_t)]
+        input_encoding: InputEncoding,
+    },
+    /// Verify the signatures of a DCAP-based quote offline.
+    VerifyQuote3 {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        collateral: CollateralArgs,
+    },
+    /// Appraise the report body of a DCAP-based quote against a policy.
+    AppraiseQuote {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Path to the TOML policy listing expected values of the report body.
+        #[arg(long)]
+        policy: String,
+    },
+    /// Verify an IAS attestation verification report offline.
+    VerifyIasReport {
+        /// Path to the raw JSON body of the report.
         #[arg(long = "in", short = 'i')]
-        filename: String,
+        in_path: String,
+        /// Path to the base64-encoded signature, i.e. the 'X-IASReport-Signature' header.
+        #[arg(long)]
+        signature: String,
+        /// Path to the PEM-encoded report signing certificate chain, i.e. the
+        /// 'X-IASReport-Signing-Certificate' header, which may be URL-encoded.
+        #[arg(long)]
+        signing_cert: String,
+        /// Path to the PEM-encoded IAS report signing CA certificate.
+        #[arg(long)]
+        ca: String,
+    },
+    /// Dump the SGX extensions of a PCK certificate in PEM or DER, such as FMSPC, PPID and TCB.
+    DumpPckCert {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Dump a REPORT, such as the one read from '/dev/attestation/report' in Gramine.
+    DumpReport {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Hex-encoded 128-bit report key to verify the MAC with.
+        #[arg(long)]
+        report_key: Option<String>,
+    },
+    /// Dump a TARGETINFO, such as the one read from '/dev/attestation/target_info' in Gramine.
+    DumpTargetInfo {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Build a TARGETINFO targeting the enclave of a SIGSTRUCT or a quote.
+    BuildTargetInfo {
+        /// Path to the SIGSTRUCT to build from.
+        #[arg(long, conflicts_with = "quote", required_unless_present = "quote")]
+        sig_struct: Option<String>,
+        /// Path to the EPID-based or SGX DCAP-based quote to build from.
+        #[arg(long)]
+        quote: Option<String>,
+        /// Path to write the raw TARGETINFO. Default dump to stdout.
+        #[arg(long, short)]
+        out: Option<String>,
+        /// Encoding of the SIGSTRUCT or quote, which is detected automatically by default.
+        #[arg(long, value_enum, default_value_t)]
+        input_encoding: InputEncoding,
+    },
+    /// Verify the signature of a SIGSTRUCT along with the values EINIT depends on.
+    VerifySigStruct {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Compute the MRENCLAVE of an enclave by replaying ECREATE, EADD and EEXTEND over its layout.
+    MeasureEnclave {
+        /// Path to the TOML layout listing the SSA frame size, the enclave size and the regions of
+        /// pages to add. Files of regions are relative to the layout.
+        #[arg(long)]
+        layout: String,
+    },
+    /// Sign an enclave into a SIGSTRUCT with an RSA-3072 key.
+    SignEnclave {
+        #[command(flatten)]
+        enclave: EnclaveArgs,
+        /// Path to the RSA-3072 private key with exponent 3 in PEM or DER, such as one generated
+        /// by 'generate-key'.
+        #[arg(long)]
+        key: String,
+        /// Path to write the raw SIGSTRUCT. Default dump to stdout.
+        #[arg(long, short)]
+        out: Option<String>,
+    },
+    /// Sign a SIGSTRUCT in two phases with a key kept elsewhere, such as in an HSM.
+    SigStruct {
+        #[command(subcommand)]
+        cmd: SigStructCmd,
     },
     /// Dump a SIGSTRUCT.
     DumpSigStruct {
+        #[command(flatten)]
+        input: InputArgs,
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        /// Dotted path of a field to print the raw value of instead of the whole dump, such as
+        /// 'body.misc_select.value'. It can be repeated, with values printed one per line.
+        #[arg(long = "field", conflicts_with = "format")]
+        fields: Vec<String>,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum SigStructCmd {
+    /// Write the data to sign, i.e. the SIGSTRUCT header followed by the body, along with the
+    /// SIGSTRUCT lacking the key, signature and q1/q2.
+    Prepare {
+        #[command(flatten)]
+        enclave: EnclaveArgs,
+        /// Path to write the data to sign with RSASSA-PKCS1-v1_5 and SHA-256, such as by
+        /// 'openssl dgst -sha256 -sign'.
+        #[arg(long)]
+        signing_data: String,
+        /// Path to write the partially filled SIGSTRUCT.
+        #[arg(long, short)]
+        out: String,
+    },
+    /// Complete a prepared SIGSTRUCT with the signature of its data and the public key, which are
+    /// verified before writing out the final SIGSTRUCT.
+    Finalize {
+        /// Path to the SIGSTRUCT written by 'prepare'.
         #[arg(long = "in", short = 'i')]
         in_path: String,
+        /// Path to the big-endian signature, as produced by 'openssl dgst -sha256 -sign'. It may
+        /// be encoded as well, which is detected automatically.
+        #[arg(long)]
+        signature: String,
+        /// Path to the RSA-3072 public key in PEM or DER.
+        #[arg(long)]
+        public_key: String,
+        /// Path to write the final SIGSTRUCT.
+        #[arg(long, short)]
+        out: String,
     },
 }
+
+/// Parses an integer in decimal, or in hex with the '0x' prefix.
+fn parse_int<T>(s: &str) -> Result<T, String>
+where
+    T: TryFrom<u64>,
+{
+    let v = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(v) => u64::from_str_radix(v, 16),
+        None => s.parse::<u64>(),
+    }
+    .map_err(|err| format!("parse integer: {err}"))?;
+
+    T::try_from(v).map_err(|_| format!("out of range: {s}"))
+}
+
+fn parse_date(s: &str) -> Result<(u16, u8, u8), String> {
+    let fields: Vec<&str> = s.split('-').collect();
+    let (year, month, day) = match fields.as_slice() {
+        [y, m, d] => (y.parse(), m.parse(), d.parse()),
+        _ => return Err("expect YYYY-MM-DD".to_string()),
+    };
+
+    match (year, month, day) {
+        (Ok(y), Ok(m @ 1..=12), Ok(d @ 1..=31)) => Ok((y, m, d)),
+        _ => Err(format!("bad date: {s}")),
+    }
+}
@@ -1,24 +1,222 @@
 use std::fs::{self, File};
-use std::io;
+use std::io::{self, Read, Stdout, Write};
+use std::path::Path;
 
 use crate::app;
-use crate::app::types::KeyFormat;
+use crate::app::types::{
+    EnclaveSigningParams, InputEncoding, KeyFormat, OutputFormat, QuoteCollateral, TargetInfoSource,
+};
+use crate::sgx::{Attributes, MiscSelect};
+use crate::{CollateralArgs, EnclaveArgs, InputArgs};
 
 pub use app::check_sgx_availability;
 
-pub fn dump_quote(path: String) -> Result<(), String> {
-    let b = fs::read(path).map_err(|err| format!("read file: {err}"))?;
+/// Path standing for stdin.
+const STDIN_PATH: &str = "-";
+
+pub fn dump_quote(input: InputArgs) -> Result<(), String> {
+    for_each_input(input, true, |out, b, _| app::decode_and_dump_quote(out, b))
+}
+
+pub fn dump_quote3(
+    input: InputArgs,
+    format: OutputFormat,
+    fields: Vec<String>,
+) -> Result<(), String> {
+    for_each_input(input, false, |out, b, file| {
+        app::decode_and_dump_quote3(out, b, format, &fields, file)
+    })
+}
+
+pub fn diff_quote(a: String, b: String, input_encoding: InputEncoding) -> Result<(), String> {
+    if a == STDIN_PATH && b == STDIN_PATH {
+        return Err("stdin can only be read once".to_string());
+    }
+
+    let a = read_input(&a, input_encoding).map_err(|err| format!("read quote a: {err}"))?;
+    let b = read_input(&b, input_encoding).map_err(|err| format!("read quote b: {err}"))?;
+
+    let mut stdout = io::stdout();
+    app::diff_quotes(&mut stdout, &a, &b)
+}
+
+pub fn verify_quote(input: InputArgs, args: CollateralArgs) -> Result<(), String> {
+    let read_optional = |p: Option<String>, what: &str| -> Result<Option<Vec<u8>>, String> {
+        p.map(|p| fs::read(p).map_err(|err| format!("read {what}: {err}")))
+            .transpose()
+    };
+
+    let mut collateral = QuoteCollateral {
+        root_ca: read_optional(args.root_ca, "root CA")?,
+        tcb_info: read_optional(args.tcb_info, "tcbInfo")?,
+        qe_identity: read_optional(args.qe_identity, "QE identity")?,
+        tcb_signing_chain: read_optional(args.tcb_signing_chain, "TCB signing chain")?,
+        ..Default::default()
+    };
+    for p in [args.root_ca_crl, args.pck_crl].into_iter().flatten() {
+        let v = fs::read(&p).map_err(|err| format!("read CRL '{p}': {err}"))?;
+        collateral.crls.push(v);
+    }
+
+    for_each_input(input, true, |out, b, _| {
+        app::verify_quote3(out, b, &collateral)
+    })
+}
+
+pub fn appraise_quote(input: InputArgs, policy_path: String) -> Result<(), String> {
+    let policy = fs::read(policy_path).map_err(|err| format!("read policy: {err}"))?;
+
+    for_each_input(input, true, |out, b, _| {
+        app::appraise_quote(out, b, &policy)
+    })
+}
+
+pub fn verify_ias_report(
+    path: String,
+    signature_path: String,
+    signing_cert_path: String,
+    ca_path: String,
+) -> Result<(), String> {
+    let report = fs::read(path).map_err(|err| format!("read report: {err}"))?;
+    let signature = fs::read(signature_path).map_err(|err| format!("read signature: {err}"))?;
+    let signing_cert =
+        fs::read(signing_cert_path).map_err(|err| format!("read signing cert: {err}"))?;
+    let ca = fs::read(ca_path).map_err(|err| format!("read CA: {err}"))?;
 
     let mut stdout = io::stdout();
-    app::decode_and_dump_quote3(&mut stdout, &b)
+    app::verify_ias_report(&mut stdout, &report, &signature, &signing_cert, &ca)
+}
+
+pub fn dump_pck_cert(input: InputArgs) -> Result<(), String> {
+    for_each_input(input, true, |out, b, _| {
+        app::decode_and_dump_pck_cert(out, b)
+    })
 }
 
-pub fn dump_sig_struct(path: String) -> Result<(), String> {
+pub fn dump_report(input: InputArgs, report_key: Option<String>) -> Result<(), String> {
+    for_each_input(input, true, |out, b, _| {
+        app::decode_and_dump_report(out, b, report_key.as_deref())
+    })
+}
+
+pub fn dump_target_info(input: InputArgs) -> Result<(), String> {
+    for_each_input(input, true, |out, b, _| {
+        app::decode_and_dump_target_info(out, b)
+    })
+}
+
+pub fn build_target_info(
+    sig_struct_path: Option<String>,
+    quote_path: Option<String>,
+    out_path: Option<String>,
+    input_encoding: InputEncoding,
+) -> Result<(), String> {
+    let (path, source) = match (sig_struct_path, quote_path) {
+        (Some(v), _) => (v, TargetInfoSource::SigStruct),
+        (None, Some(v)) => (v, TargetInfoSource::Quote),
+        (None, None) => return Err("missing SIGSTRUCT or quote".to_string()),
+    };
     let b = fs::read(path).map_err(|err| format!("read file: {err}"))?;
+    let b = app::decode_input(&b, input_encoding).map_err(|err| format!("decode input: {err}"))?;
+
+    match out_path {
+        Some(v) => {
+            let mut out = File::create(v).map_err(|err| format!("open file: {err}"))?;
+            app::build_and_encode_target_info(&mut out, &b, source, true)
+        }
+        None => {
+            let mut out = io::stdout();
+            app::build_and_encode_target_info(&mut out, &b, source, false)
+        }
+    }
+}
+
+pub fn dump_sig_struct(
+    input: InputArgs,
+    format: OutputFormat,
+    fields: Vec<String>,
+) -> Result<(), String> {
+    for_each_input(input, false, |out, b, file| {
+        app::decode_and_dump_sig_struct(out, b, format, &fields, file)
+            .map_err(|err| format!("decode and dump: {err}"))
+    })
+}
+
+pub fn measure_enclave(layout_path: String) -> Result<(), String> {
+    let layout = fs::read(&layout_path).map_err(|err| format!("read layout: {err}"))?;
+    let dir = Path::new(&layout_path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    let mut stdout = io::stdout();
+    app::measure_enclave(&mut stdout, &layout, |p| {
+        fs::read(dir.join(p)).map_err(|err| format!("read file: {err}"))
+    })
+}
+
+pub fn sign_enclave(
+    enclave: EnclaveArgs,
+    key_path: String,
+    out_path: Option<String>,
+) -> Result<(), String> {
+    let params = enclave_signing_params(enclave)?;
+    let key = fs::read(key_path).map_err(|err| format!("read key: {err}"))?;
+
+    match out_path {
+        Some(v) => {
+            let mut out = File::create(v).map_err(|err| format!("open file: {err}"))?;
+            app::sign_and_encode_sig_struct(&mut out, &params, &key, true)
+        }
+        None => {
+            let mut out = io::stdout();
+            app::sign_and_encode_sig_struct(&mut out, &params, &key, false)
+        }
+    }
+}
+
+pub fn prepare_sig_struct(
+    enclave: EnclaveArgs,
+    signing_data_path: String,
+    out_path: String,
+) -> Result<(), String> {
+    let params = enclave_signing_params(enclave)?;
+
+    let mut data_out =
+        File::create(signing_data_path).map_err(|err| format!("open signing data: {err}"))?;
+    let mut out = File::create(out_path).map_err(|err| format!("open file: {err}"))?;
+
+    app::prepare_sig_struct(&mut data_out, &mut out, &params)
+}
+
+pub fn finalize_sig_struct(
+    path: String,
+    signature_path: String,
+    public_key_path: String,
+    out_path: String,
+) -> Result<(), String> {
+    let ss = fs::read(path).map_err(|err| format!("read SIGSTRUCT: {err}"))?;
+    let signature = fs::read(signature_path).map_err(|err| format!("read signature: {err}"))?;
+    let signature = app::decode_input(&signature, InputEncoding::Auto)
+        .map_err(|err| format!("decode signature: {err}"))?;
+    let public_key = fs::read(public_key_path).map_err(|err| format!("read public key: {err}"))?;
+
+    // Buffered so that no file is left behind if the signature is rejected.
+    let mut out = vec![];
+    app::finalize_sig_struct(&mut out, &ss, &signature, &public_key)?;
+
+    fs::write(out_path, out).map_err(|err| format!("write file: {err}"))
+}
+
+pub fn verify_sig_struct(input: InputArgs) -> Result<(), String> {
+    for_each_input(input, true, |out, b, _| app::verify_sig_struct(out, b))
+}
+
+pub fn key_mrsigner(path: String) -> Result<(), String> {
+    let b = fs::read(path).map_err(|err| format!("read key: {err}"))?;
 
     let mut stdout = io::stdout();
-    app::decode_and_dump_sig_struct(&mut stdout, &b)
-        .map_err(|err| format!("decode and dump: {err}"))
+    app::decode_and_dump_key_mrsigner(&mut stdout, &b)
 }
 
 pub fn generate_key(out_path: Option<String>) -> Result<(), String> {
@@ -42,3 +240,138 @@ pub fn generate_key(out_path: Option<String>) -> Result<(), String> {
 
     app::generate_and_encode_key(&mut out, f)
 }
+
+fn enclave_signing_params(args: EnclaveArgs) -> Result<EnclaveSigningParams, String> {
+    let mr_enclave = app::decode_hex(&args.mr_enclave)
+        .map_err(|err| format!("decode MRENCLAVE: {err}"))?
+        .try_into()
+        .map_err(|v: Vec<u8>| format!("bad MRENCLAVE length: expect 32, got {}", v.len()))?;
+
+    let out = EnclaveSigningParams {
+        mr_enclave,
+        attributes: Attributes {
+            flags: args.attributes,
+            xfrm: args.xfrm,
+        },
+        attribute_mask: Attributes {
+            flags: args.attribute_mask,
+            xfrm: args.xfrm_mask,
+        },
+        misc_select: MiscSelect(args.misc_select),
+        misc_mask: MiscSelect(args.misc_mask),
+        isv_prod_id: args.isv_prod_id,
+        isv_svn: args.isv_svn,
+        debug: args.debug,
+        date: args.date,
+    };
+
+    Ok(out)
+}
+
+/// Runs `f` over every input listed in `args`, with stdout and the decoded input.
+///
+/// A single input is handed over as is. Otherwise, `f` is also given the path of each input,
+/// records are separated by empty lines and headed by their paths if `header` is set, failures
+/// are reported to stderr without stopping the remaining inputs and a summary ends the run.
+fn for_each_input<F>(args: InputArgs, header: bool, mut f: F) -> Result<(), String>
+where
+    F: FnMut(&mut Stdout, &[u8], Option<&str>) -> Result<(), String>,
+{
+    let paths = expand_paths(&args.paths)?;
+
+    let mut stdout = io::stdout();
+    if paths == args.paths && paths.len() == 1 {
+        let b = read_input(&paths[0], args.input_encoding)?;
+        return f(&mut stdout, &b, None);
+    }
+
+    let mut failed = 0;
+    for (i, path) in paths.iter().enumerate() {
+        let file = if path == STDIN_PATH { "<stdin>" } else { path };
+
+        if i != 0 {
+            writeln!(stdout).map_err(|err| format!("write: {err}"))?;
+        }
+        if header {
+            app::write_record_header(&mut stdout, file)?;
+        }
+
+        let result =
+            read_input(path, args.input_encoding).and_then(|b| f(&mut stdout, &b, Some(file)));
+        if let Err(err) = result {
+            eprintln!("{file}: {err}");
+            failed += 1;
+        }
+    }
+
+    eprintln!(
+        "{} input(s) processed: {} ok, {failed} failed",
+        paths.len(),
+        paths.len() - failed
+    );
+    if failed != 0 {
+        return Err(format!("{failed} of {} input(s) failed", paths.len()));
+    }
+
+    Ok(())
+}
+
+/// Expands directories into the files inside and glob patterns into the matched paths, in
+/// lexical order. Other paths are kept as is.
+fn expand_paths(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut out = vec![];
+
+    for p in paths {
+        if p == STDIN_PATH {
+            if out.iter().any(|v| v == STDIN_PATH) {
+                return Err("stdin can only be read once".to_string());
+            }
+            out.push(p.clone());
+            continue;
+        }
+
+        let mut matched = if Path::new(p).is_dir() {
+            fs::read_dir(p)
+                .map_err(|err| format!("read dir '{p}': {err}"))?
+                .map(|v| v.map(|v| v.path()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("read dir '{p}': {err}"))?
+                .into_iter()
+                .filter(|v| v.is_file())
+                .collect::<Vec<_>>()
+        } else if p.contains(['*', '?', '[']) {
+            glob::glob(p)
+                .map_err(|err| format!("parse pattern '{p}': {err}"))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("glob '{p}': {err}"))?
+                .into_iter()
+                .filter(|v| v.is_file())
+                .collect::<Vec<_>>()
+        } else {
+            out.push(p.clone());
+            continue;
+        };
+
+        if matched.is_empty() {
+            return Err(format!("no input found by '{p}'"));
+        }
+        matched.sort();
+        out.extend(matched.iter().map(|v| v.display().to_string()));
+    }
+
+    Ok(out)
+}
+
+fn read_input(path: &str, encoding: InputEncoding) -> Result<Vec<u8>, String> {
+    let b = if path == STDIN_PATH {
+        let mut b = vec![];
+        io::stdin()
+            .read_to_end(&mut b)
+            .map_err(|err| format!("read stdin: {err}"))?;
+        b
+    } else {
+        fs::read(path).map_err(|err| format!("read file: {err}"))?
+    };
+
+    app::decode_input(&b, encoding).map_err(|err| format!("decode input: {err}"))
+}
@@ -1,8 +1,10 @@
 use std::{arch::asm, fmt::Display};
 
+use serde::Serialize;
+
 use crate::sgx;
 
-#[derive(Default)]
+#[derive(Default, Serialize)]
 pub struct Checker {
     pub cpuid_supported: bool,
     pub from_intel: bool,
@@ -11,7 +13,7 @@ pub struct Checker {
     pub sgx2_supported: bool,
     pub flc_supported: bool,
     pub sgx_virt_supported: bool,
-    pub sgx_memsgx_mem_concurrency_supported: bool,
+    pub sgx_mem_concurrency_supported: bool,
     pub cet_supported: bool,
     pub kss_supported: bool,
     pub maximum_enclave_size_x86: u64,
@@ -59,7 +61,7 @@ impl Checker {
         out.sgx1_supported = (id_18_0.eax & (1 << 0)) != 0;
         out.sgx2_supported = (id_18_0.eax & (1 << 1)) != 0;
         out.sgx_virt_supported = (id_18_0.eax & (1 << 5)) != 0;
-        out.sgx_memsgx_mem_concurrency_supported = (id_18_0.eax & (1 << 6)) != 0;
+        out.sgx_mem_concurrency_supported = (id_18_0.eax & (1 << 6)) != 0;
 
         out.cet_supported = (id_18_1.eax & (1 << 6)) != 0;
         out.kss_supported = (id_18_1.eax & (1 << 7)) != 0;
@@ -130,7 +132,7 @@ impl Display for Checker {
         writeln!(
             f,
             "Extensions for concurrent memory management (ETRACKC, ELDBC, ELDUC, ERDINFO): {}",
-            self.sgx_memsgx_mem_concurrency_supported
+            self.sgx_mem_concurrency_supported
         )
         .unwrap();
 
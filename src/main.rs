@@ -1,14 +1,62 @@
 use clap::Parser;
 
-use gramine_cli::{cmd, Cli, Cmd};
+use gramine_cli::{cmd, Cli, Cmd, SigStructCmd};
 
 fn main() -> Result<(), String> {
     let cli = Cli::parse();
 
     match cli.cmd {
         Cmd::GenerateKey { out } => cmd::generate_key(out),
-        Cmd::IsSgxAvailable { quite } => cmd::check_sgx_availability(quite),
-        Cmd::DumpQuote3 { filename } => cmd::dump_quote(filename),
-        Cmd::DumpSigStruct { in_path } => cmd::dump_sig_struct(in_path),
+        Cmd::KeyMrsigner { in_path } => cmd::key_mrsigner(in_path),
+        Cmd::IsSgxAvailable { quite, format } => cmd::check_sgx_availability(quite, format),
+        Cmd::DumpQuote { input } => cmd::dump_quote(input),
+        Cmd::DumpQuote3 {
+            input,
+            format,
+            fields,
+        } => cmd::dump_quote3(input, format, fields),
+        Cmd::DiffQuote {
+            a,
+            b,
+            input_encoding,
+        } => cmd::diff_quote(a, b, input_encoding),
+        Cmd::VerifyQuote3 { input, collateral } => cmd::verify_quote(input, collateral),
+        Cmd::AppraiseQuote { input, policy } => cmd::appraise_quote(input, policy),
+        Cmd::VerifyIasReport {
+            in_path,
+            signature,
+            signing_cert,
+            ca,
+        } => cmd::verify_ias_report(in_path, signature, signing_cert, ca),
+        Cmd::DumpPckCert { input } => cmd::dump_pck_cert(input),
+        Cmd::DumpReport { input, report_key } => cmd::dump_report(input, report_key),
+        Cmd::DumpTargetInfo { input } => cmd::dump_target_info(input),
+        Cmd::BuildTargetInfo {
+            sig_struct,
+            quote,
+            out,
+            input_encoding,
+        } => cmd::build_target_info(sig_struct, quote, out, input_encoding),
+        Cmd::DumpSigStruct {
+            input,
+            format,
+            fields,
+        } => cmd::dump_sig_struct(input, format, fields),
+        Cmd::MeasureEnclave { layout } => cmd::measure_enclave(layout),
+        Cmd::SignEnclave { enclave, key, out } => cmd::sign_enclave(enclave, key, out),
+        Cmd::SigStruct { cmd } => match cmd {
+            SigStructCmd::Prepare {
+                enclave,
+                signing_data,
+                out,
+            } => cmd::prepare_sig_struct(enclave, signing_data, out),
+            SigStructCmd::Finalize {
+                in_path,
+                signature,
+                public_key,
+                out,
+            } => cmd::finalize_sig_struct(in_path, signature, public_key, out),
+        },
+        Cmd::VerifySigStruct { input } => cmd::verify_sig_struct(input),
     }
 }
@@ -0,0 +1,173 @@
+use std::fmt::Display;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+pub const ATTRIBUTE_INIT: u64 = 1 << 0;
+pub const ATTRIBUTE_DEBUG: u64 = 1 << 1;
+pub const ATTRIBUTE_MODE64BIT: u64 = 1 << 2;
+pub const ATTRIBUTE_PROVISIONKEY: u64 = 1 << 4;
+pub const ATTRIBUTE_EINITTOKEN_KEY: u64 = 1 << 5;
+pub const ATTRIBUTE_CET: u64 = 1 << 6;
+pub const ATTRIBUTE_KSS: u64 = 1 << 7;
+pub const ATTRIBUTE_AEXNOTIFY: u64 = 1 << 10;
+
+/// Named bits of the ATTRIBUTES flags.
+///
+/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/sgx_attributes.h
+const ATTRIBUTE_NAMES: &[(u64, &str)] = &[
+    (ATTRIBUTE_INIT, "INIT"),
+    (ATTRIBUTE_DEBUG, "DEBUG"),
+    (ATTRIBUTE_MODE64BIT, "MODE64BIT"),
+    (ATTRIBUTE_PROVISIONKEY, "PROVISIONKEY"),
+    (ATTRIBUTE_EINITTOKEN_KEY, "EINITTOKEN_KEY"),
+    (ATTRIBUTE_CET, "CET"),
+    (ATTRIBUTE_KSS, "KSS"),
+    (ATTRIBUTE_AEXNOTIFY, "AEXNOTIFY"),
+];
+
+/// Named bits of XFRM, i.e. the XSAVE feature set.
+///
+/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/sgx_attributes.h
+const XFRM_NAMES: &[(u64, &str)] = &[
+    (1 << 0, "X87"),
+    (1 << 1, "SSE"),
+    (1 << 2, "AVX"),
+    (1 << 3, "MPX_BNDREGS"),
+    (1 << 4, "MPX_BNDCSR"),
+    (1 << 5, "AVX512_OPMASK"),
+    (1 << 6, "AVX512_ZMM_HI256"),
+    (1 << 7, "AVX512_HI16_ZMM"),
+    (1 << 9, "PKRU"),
+    (1 << 17, "AMX_TILECFG"),
+    (1 << 18, "AMX_TILEDATA"),
+];
+
+pub const MISC_EXINFO: u32 = 1 << 0;
+pub const MISC_CPINFO: u32 = 1 << 1;
+
+/// Named bits of MISCSELECT, i.e. the extended information reported into the SSA frame on AEX.
+const MISC_NAMES: &[(u64, &str)] = &[
+    (MISC_EXINFO as u64, "EXINFO"),
+    (MISC_CPINFO as u64, "CPINFO"),
+];
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C, packed)]
+pub struct Attributes {
+    pub flags: u64,
+    pub xfrm: u64,
+}
+
+impl Attributes {
+    /// Names of the set flag bits. Unknown bits are merged into a trailing hex value.
+    pub fn flag_names(&self) -> Vec<String> {
+        bit_names(self.flags, ATTRIBUTE_NAMES)
+    }
+
+    /// Names of the set XFRM bits. Unknown bits are merged into a trailing hex value.
+    pub fn xfrm_names(&self) -> Vec<String> {
+        bit_names(self.xfrm, XFRM_NAMES)
+    }
+
+    pub fn is_debug(&self) -> bool {
+        (self.flags & ATTRIBUTE_DEBUG) != 0
+    }
+}
+
+impl Display for Attributes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (flags, xfrm) = (self.flags, self.xfrm);
+        write!(
+            f,
+            "flags={:#018x} [{}], xfrm={:#018x} [{}]",
+            flags,
+            self.flag_names().join(", "),
+            xfrm,
+            self.xfrm_names().join(", ")
+        )
+    }
+}
+
+/// MISCSELECT, where EXINFO enables reporting #PF/#GP exception info and CPINFO enables reporting
+/// #CP exception info.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct MiscSelect(pub u32);
+
+impl MiscSelect {
+    /// Names of the set bits. Unknown bits are merged into a trailing hex value.
+    pub fn names(self) -> Vec<String> {
+        bit_names(self.0 as u64, MISC_NAMES)
+    }
+
+    /// Describes each named bit enforced by `mask` as either required ('on') or forbidden ('off').
+    pub fn enforced_by(self, mask: MiscSelect) -> Vec<String> {
+        MISC_NAMES
+            .iter()
+            .filter(|(bit, _)| ((mask.0 as u64) & bit) != 0)
+            .map(|(bit, name)| {
+                let state = if ((self.0 as u64) & bit) != 0 {
+                    "on"
+                } else {
+                    "off"
+                };
+                format!("{name}={state}")
+            })
+            .collect()
+    }
+}
+
+impl Display for MiscSelect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let v = *self;
+        write!(f, "{:#010x} [{}]", v.0, v.names().join(", "))
+    }
+}
+
+/// Serialized as the hex-encoded value along with the names of set bits, where the hex string
+/// avoids overflowing the signed 64-bit integers of TOML.
+impl Serialize for Attributes {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (flags, xfrm) = (self.flags, self.xfrm);
+
+        let mut out = s.serialize_struct("Attributes", 4)?;
+        out.serialize_field("flags", &format!("{flags:#018x}"))?;
+        out.serialize_field("flag_names", &self.flag_names())?;
+        out.serialize_field("xfrm", &format!("{xfrm:#018x}"))?;
+        out.serialize_field("xfrm_names", &self.xfrm_names())?;
+        out.end()
+    }
+}
+
+impl Serialize for MiscSelect {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let v = *self;
+
+        let mut out = s.serialize_struct("MiscSelect", 2)?;
+        out.serialize_field("value", &format!("{:#010x}", v.0))?;
+        out.serialize_field("names", &v.names())?;
+        out.end()
+    }
+}
+
+fn bit_names(v: u64, names: &[(u64, &str)]) -> Vec<String> {
+    let mut out: Vec<String> = names
+        .iter()
+        .filter(|(bit, _)| (v & bit) != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+
+    let unknown = names.iter().fold(v, |acc, (bit, _)| acc & !bit);
+    if unknown != 0 {
+        out.push(format!("{unknown:#x}"));
+    }
+
+    out
+}
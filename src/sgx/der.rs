@@ -0,0 +1,166 @@
+pub const TAG_BOOLEAN: u8 = 0x01;
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_ENUMERATED: u8 = 0x0a;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tlv<'a> {
+    pub tag: u8,
+    pub value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Reads one TLV from the front of `b`, returning it along with the trailing bytes.
+    pub fn read(b: &'a [u8]) -> Result<(Self, &'a [u8]), String> {
+        if b.len() < 2 {
+            return Err(format!("bad TLV length: expect>=2, got={}", b.len()));
+        }
+
+        let tag = b[0];
+        if (tag & 0x1f) == 0x1f {
+            return Err(format!("unsupported multi-byte tag: {tag:#04x}"));
+        }
+
+        let (len, v) = match b[1] {
+            n if n < 0x80 => (n as usize, &b[2..]),
+            0x80 => return Err("indefinite length isn't allowed in DER".to_string()),
+            n => {
+                let n = (n & 0x7f) as usize;
+                if n > std::mem::size_of::<u32>() || b.len() < 2 + n {
+                    return Err(format!("bad long-form length of {n} bytes"));
+                }
+                let len = b[2..(2 + n)]
+                    .iter()
+                    .fold(0usize, |acc, v| (acc << 8) | (*v as usize));
+                (len, &b[(2 + n)..])
+            }
+        };
+
+        if v.len() < len {
+            let hint = format!("bad value length: expect>={}, got={}", len, v.len());
+            return Err(hint);
+        }
+
+        Ok((
+            Self {
+                tag,
+                value: &v[..len],
+            },
+            &v[len..],
+        ))
+    }
+
+    /// Reads one TLV from the front of `b` and checks its tag.
+    pub fn read_tagged(b: &'a [u8], tag: u8) -> Result<(Self, &'a [u8]), String> {
+        let (out, rest) = Self::read(b)?;
+        if out.tag != tag {
+            return Err(format!("bad tag: expect {tag:#04x}, got {:#04x}", out.tag));
+        }
+
+        Ok((out, rest))
+    }
+
+    /// Splits the value of a constructed TLV into its children.
+    pub fn children(&self) -> Result<Vec<Tlv<'a>>, String> {
+        let mut out = vec![];
+
+        let mut v = self.value;
+        while !v.is_empty() {
+            let (child, rest) = Self::read(v)?;
+            out.push(child);
+            v = rest;
+        }
+
+        Ok(out)
+    }
+
+    pub fn as_bool(&self) -> Result<bool, String> {
+        self.expect_tag(TAG_BOOLEAN)?;
+        match self.value {
+            [v] => Ok(*v != 0),
+            _ => Err(format!("bad BOOLEAN length: {}", self.value.len())),
+        }
+    }
+
+    /// Decodes a non-negative INTEGER or ENUMERATED which fits into u64.
+    pub fn as_u64(&self) -> Result<u64, String> {
+        if self.tag != TAG_INTEGER && self.tag != TAG_ENUMERATED {
+            return Err(format!("bad tag for integer: {:#04x}", self.tag));
+        }
+
+        match self.value.first() {
+            None => return Err("empty integer".to_string()),
+            Some(v) if (v & 0x80) != 0 => return Err("negative integer".to_string()),
+            _ => {}
+        }
+
+        let v = match self.value.iter().position(|v| *v != 0) {
+            Some(i) => &self.value[i..],
+            None => &[],
+        };
+        if v.len() > std::mem::size_of::<u64>() {
+            return Err(format!("integer too large: {} bytes", v.len()));
+        }
+
+        Ok(v.iter().fold(0u64, |acc, v| (acc << 8) | (*v as u64)))
+    }
+
+    pub fn as_octet_string(&self) -> Result<&'a [u8], String> {
+        self.expect_tag(TAG_OCTET_STRING)?;
+        Ok(self.value)
+    }
+
+    fn expect_tag(&self, tag: u8) -> Result<(), String> {
+        if self.tag != tag {
+            return Err(format!("bad tag: expect {tag:#04x}, got {:#04x}", self.tag));
+        }
+
+        Ok(())
+    }
+}
+
+/// Looks up the value of the extension identified by the DER-encoded `oid` in a DER-encoded
+/// X.509 certificate.
+pub fn find_extension<'a>(cert: &'a [u8], oid: &[u8]) -> Result<Option<&'a [u8]>, String> {
+    let (cert, _) =
+        Tlv::read_tagged(cert, TAG_SEQUENCE).map_err(|err| format!("read cert: {err}"))?;
+    let (tbs, _) =
+        Tlv::read_tagged(cert.value, TAG_SEQUENCE).map_err(|err| format!("read TBS: {err}"))?;
+
+    // Extensions are wrapped in the explicit context-specific tag [3].
+    let fields = tbs
+        .children()
+        .map_err(|err| format!("read TBS fields: {err}"))?;
+    let extensions = match fields.iter().find(|v| v.tag == 0xa3) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let (extensions, _) = Tlv::read_tagged(extensions.value, TAG_SEQUENCE)
+        .map_err(|err| format!("read extensions: {err}"))?;
+
+    for ext in extensions
+        .children()
+        .map_err(|err| format!("read extensions: {err}"))?
+    {
+        let fields = ext
+            .children()
+            .map_err(|err| format!("read extension: {err}"))?;
+
+        let (id, value) = match (fields.first(), fields.last()) {
+            (Some(id), Some(value)) if fields.len() >= 2 => (id, value),
+            _ => return Err("bad extension: too few fields".to_string()),
+        };
+        if id.tag != TAG_OID || id.value != oid {
+            continue;
+        }
+
+        return value
+            .as_octet_string()
+            .map(Some)
+            .map_err(|err| format!("read extnValue: {err}"));
+    }
+
+    Ok(None)
+}
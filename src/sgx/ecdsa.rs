@@ -0,0 +1,530 @@
+use std::fmt::Display;
+
+use encoding::hex;
+use serde::{Serialize, Serializer};
+
+use super::{fixed_length_pad, ser, validate_minimum_length, ReportBody, LENGTH_REPORT_BODY};
+
+const LENGTH_ECDSA_SIG: usize = 64;
+const LENGTH_ECDSA_PUB_KEY: usize = 64;
+const LENGTH_PPID: usize = 16;
+const LENGTH_PPID_RSA2048_ENCRYPTED: usize = 256;
+const LENGTH_PPID_RSA3072_ENCRYPTED: usize = 384;
+
+pub const CERT_KEY_TYPE_PPID_CLEARTEXT: u16 = 1;
+pub const CERT_KEY_TYPE_PPID_RSA2048_ENCRYPTED: u16 = 2;
+pub const CERT_KEY_TYPE_PPID_RSA3072_ENCRYPTED: u16 = 3;
+pub const CERT_KEY_TYPE_PCK_CLEARTEXT: u16 = 4;
+pub const CERT_KEY_TYPE_PCK_CERT_CHAIN: u16 = 5;
+pub const CERT_KEY_TYPE_QE_REPORT_CERT_DATA: u16 = 6;
+pub const CERT_KEY_TYPE_ECDSA_SIG_AUX_DATA: u16 = 7;
+
+/// Decoded payload of [`CertificationData`], selected by `cert_key_type`.
+///
+/// ref: https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_SGX_ECDSA_QuoteLibReference_DCAP_API.pdf
+/// (Appendix A, Certification Data)
+#[derive(Clone, Debug)]
+pub enum CertData {
+    /// Type 1: PPID in plain text, with CPUSVN, PCESVN and PCE-ID.
+    PpidCleartext(PckIdentifier),
+    /// Type 2: PPID encrypted with RSA-2048-OAEP, with CPUSVN, PCESVN and PCE-ID.
+    PpidRsa2048Encrypted(PckIdentifier),
+    /// Type 3: PPID encrypted with RSA-3072-OAEP, with CPUSVN, PCESVN and PCE-ID.
+    PpidRsa3072Encrypted(PckIdentifier),
+    /// Type 4: PCK leaf certificate.
+    PckLeafCert(Vec<u8>),
+    /// Type 5: concatenated PEM of PCK leaf cert, intermediate CA cert and root CA cert.
+    PckCertChain(Vec<u8>),
+    /// Type 6: QE report certification data, carried by version 4 quotes.
+    QeReport(Box<QeReportCertData>),
+    /// Type 7: ECDSA signature auxiliary data, kept opaque.
+    EcdsaSigAuxData(Vec<u8>),
+}
+
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.14/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_3.h#L163
+#[derive(Clone, Debug, Serialize)]
+pub struct CertificationData {
+    pub cert_key_type: u16,
+    pub size: u32,
+    pub data: CertData,
+}
+
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.14/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_3.h#L151
+#[derive(Clone, Debug, Serialize)]
+pub struct EcdsaSigData {
+    /// ECDSA signature over the quote header and the ISV enclave report body.
+    #[serde(serialize_with = "ser::hex")]
+    pub sig: [u8; LENGTH_ECDSA_SIG],
+    /// Raw EC public key (x||y) of the attestation key.
+    #[serde(serialize_with = "ser::hex")]
+    pub attest_pub_key: [u8; LENGTH_ECDSA_PUB_KEY],
+    pub qe_report: ReportBody,
+    /// ECDSA signature over the QE report body, made by the PCK.
+    #[serde(serialize_with = "ser::hex")]
+    pub qe_report_sig: [u8; LENGTH_ECDSA_SIG],
+    pub qe_auth_data: QeAuthData,
+    pub certification_data: CertificationData,
+}
+
+/// PCK identifier carried by certification data types 1 to 3. `ppid` is the plain PPID for type 1,
+/// or the RSA-OAEP encrypted PPID for types 2 and 3.
+#[derive(Clone, Debug, Serialize)]
+pub struct PckIdentifier {
+    #[serde(serialize_with = "ser::hex")]
+    pub ppid: Vec<u8>,
+    #[serde(serialize_with = "ser::hex")]
+    pub cpu_svn: [u8; 16],
+    pub pce_svn: u16,
+    pub pce_id: u16,
+}
+
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.14/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_3.h#L144
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct QeAuthData {
+    pub size: u16,
+    #[serde(serialize_with = "ser::hex")]
+    pub data: Vec<u8>,
+}
+
+/// Certification data type 6, which wraps the QE report and the PCK certification data.
+#[derive(Clone, Debug, Serialize)]
+pub struct QeReportCertData {
+    pub qe_report: ReportBody,
+    #[serde(serialize_with = "ser::hex")]
+    pub qe_report_sig: [u8; LENGTH_ECDSA_SIG],
+    pub qe_auth_data: QeAuthData,
+    pub certification_data: CertificationData,
+}
+
+impl CertData {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PpidCleartext(_) => "PPID_CLEARTEXT",
+            Self::PpidRsa2048Encrypted(_) => "PPID_RSA2048_ENCRYPTED",
+            Self::PpidRsa3072Encrypted(_) => "PPID_RSA3072_ENCRYPTED",
+            Self::PckLeafCert(_) => "PCK_CLEARTEXT",
+            Self::PckCertChain(_) => "PCK_CERT_CHAIN",
+            Self::QeReport(_) => "QE_REPORT_CERT_DATA",
+            Self::EcdsaSigAuxData(_) => "ECDSA_SIG_AUX_DATA",
+        }
+    }
+
+    /// Decodes `data` as certification data of type `cert_key_type`.
+    pub fn decode(cert_key_type: u16, data: &[u8]) -> Result<Self, String> {
+        let out = match cert_key_type {
+            CERT_KEY_TYPE_PPID_CLEARTEXT => {
+                Self::PpidCleartext(PckIdentifier::decode(data, LENGTH_PPID)?)
+            }
+            CERT_KEY_TYPE_PPID_RSA2048_ENCRYPTED => Self::PpidRsa2048Encrypted(
+                PckIdentifier::decode(data, LENGTH_PPID_RSA2048_ENCRYPTED)?,
+            ),
+            CERT_KEY_TYPE_PPID_RSA3072_ENCRYPTED => Self::PpidRsa3072Encrypted(
+                PckIdentifier::decode(data, LENGTH_PPID_RSA3072_ENCRYPTED)?,
+            ),
+            CERT_KEY_TYPE_PCK_CLEARTEXT => Self::PckLeafCert(data.to_vec()),
+            CERT_KEY_TYPE_PCK_CERT_CHAIN => Self::PckCertChain(data.to_vec()),
+            CERT_KEY_TYPE_QE_REPORT_CERT_DATA => {
+                let v = QeReportCertData::try_from(data)
+                    .map_err(|err| format!("parse QE report certification data: {err}"))?;
+                Self::QeReport(Box::new(v))
+            }
+            CERT_KEY_TYPE_ECDSA_SIG_AUX_DATA => Self::EcdsaSigAuxData(data.to_vec()),
+            v => return Err(format!("unsupported cert_key_type: {v}")),
+        };
+
+        Ok(out)
+    }
+}
+
+impl Display for CertData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PpidCleartext(v)
+            | Self::PpidRsa2048Encrypted(v)
+            | Self::PpidRsa3072Encrypted(v) => {
+                write!(f, "{v}")
+            }
+            Self::PckLeafCert(v) => write!(
+                f,
+                "{} = {}",
+                fixed_length_pad("pck_cert", 14),
+                pem_or_hex(v)
+            ),
+            Self::PckCertChain(v) => write!(
+                f,
+                "{} = {}",
+                fixed_length_pad("pck_cert_chain", 14),
+                pem_or_hex(v)
+            ),
+            Self::QeReport(v) => write!(f, "{v}"),
+            Self::EcdsaSigAuxData(v) => write!(
+                f,
+                "{} = {}",
+                fixed_length_pad("aux_data", 14),
+                hex::encode_to_string(v.as_ref())
+            ),
+        }
+    }
+}
+
+/// PCK certificates are serialized as PEM text, and other opaque data as hex.
+impl Serialize for CertData {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::PpidCleartext(v)
+            | Self::PpidRsa2048Encrypted(v)
+            | Self::PpidRsa3072Encrypted(v) => v.serialize(s),
+            Self::PckLeafCert(v) | Self::PckCertChain(v) => {
+                match std::str::from_utf8(trim_nul(v)) {
+                    Ok(v) if v.starts_with("-----BEGIN") => s.serialize_str(v),
+                    _ => ser::hex(v, s),
+                }
+            }
+            Self::QeReport(v) => v.serialize(s),
+            Self::EcdsaSigAuxData(v) => ser::hex(v, s),
+        }
+    }
+}
+
+impl CertificationData {
+    /// Returns the PEM-encoded PCK certificate(s) carried by types 4 and 5, looking into the nested
+    /// certification data of type 6 if necessary.
+    pub fn pck_certs(&self) -> Option<&[u8]> {
+        match &self.data {
+            CertData::PckLeafCert(v) | CertData::PckCertChain(v) => Some(trim_nul(v)),
+            CertData::QeReport(v) => v.certification_data.pck_certs(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CertificationData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 13) };
+
+        writeln!(
+            f,
+            "{} = {} # {}",
+            pad("cert_key_type"),
+            self.cert_key_type,
+            self.data.name()
+        )
+        .expect("write cert_key_type");
+        writeln!(f, "{} = {}", pad("size"), self.size).expect("write size");
+        write!(f, "{}", self.data).expect("write data");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for CertificationData {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), 6).map_err(|err| format!("validate length: {err}"))?;
+
+        let cert_key_type = u16::from_le_bytes([value[0], value[1]]);
+        let size = u32::from_le_bytes(value[2..6].try_into().expect("parse size"));
+
+        let v = &value[6..];
+        if v.len() != (size as usize) {
+            let hint = format!("bad data length: expect {}, got {}", size, v.len());
+            return Err(hint);
+        }
+
+        let data =
+            CertData::decode(cert_key_type, v).map_err(|err| format!("decode data: {err}"))?;
+
+        let out = Self {
+            cert_key_type,
+            size,
+            data,
+        };
+
+        Ok(out)
+    }
+}
+
+impl Display for EcdsaSigData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 14) };
+
+        writeln!(
+            f,
+            "{} = {}",
+            pad("sig"),
+            hex::encode_to_string(self.sig.as_ref())
+        )
+        .expect("write sig");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("attest_pub_key"),
+            hex::encode_to_string(self.attest_pub_key.as_ref())
+        )
+        .expect("write attest_pub_key");
+
+        // QE report fields are dumped along with the certification data of type 6 in this case.
+        if !matches!(self.certification_data.data, CertData::QeReport(_)) {
+            writeln!(
+                f,
+                "{} = {}",
+                pad("qe_report_sig"),
+                hex::encode_to_string(self.qe_report_sig.as_ref())
+            )
+            .expect("write qe_report_sig");
+            writeln!(f).unwrap();
+
+            writeln!(f, "[sig.qe_report]").unwrap();
+            writeln!(f, "{}", self.qe_report).expect("write qe_report");
+            writeln!(f).unwrap();
+
+            writeln!(f, "[sig.qe_auth_data]").unwrap();
+            writeln!(f, "{}", self.qe_auth_data).expect("write qe_auth_data");
+        }
+        writeln!(f).unwrap();
+
+        writeln!(f, "[sig.certification_data]").unwrap();
+        write!(f, "{}", self.certification_data).expect("write certification_data");
+
+        Ok(())
+    }
+}
+
+impl EcdsaSigData {
+    /// Parses the signature data of version 4 quotes, i.e. `sgx_ql_ecdsa_sig_data_v4_t`, where the
+    /// QE report comes with certification data of type 6. The QE report fields are lifted from the
+    /// nested certification data, which is kept as is in `certification_data`.
+    pub fn decode_v4(value: &[u8]) -> Result<Self, String> {
+        validate_minimum_length(value.len(), LENGTH_ECDSA_SIG + LENGTH_ECDSA_PUB_KEY)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let (sig, v) = value.split_at(LENGTH_ECDSA_SIG);
+        let (attest_pub_key, v) = v.split_at(LENGTH_ECDSA_PUB_KEY);
+
+        let certification_data = CertificationData::try_from(v)
+            .map_err(|err| format!("parse certification_data: {err}"))?;
+        let qe = match &certification_data.data {
+            CertData::QeReport(v) => v,
+            v => {
+                let hint = format!(
+                    "bad certification data: expect QE_REPORT_CERT_DATA, got {}",
+                    v.name()
+                );
+                return Err(hint);
+            }
+        };
+
+        let out = Self {
+            sig: sig.try_into().expect("parse sig"),
+            attest_pub_key: attest_pub_key.try_into().expect("parse attest_pub_key"),
+            qe_report: qe.qe_report,
+            qe_report_sig: qe.qe_report_sig,
+            qe_auth_data: qe.qe_auth_data.clone(),
+            certification_data,
+        };
+
+        Ok(out)
+    }
+}
+
+impl TryFrom<&[u8]> for EcdsaSigData {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_ECDSA_SIG + LENGTH_ECDSA_PUB_KEY)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let mut v = value;
+
+        let sig = v[..LENGTH_ECDSA_SIG].try_into().expect("parse sig");
+        v = &v[LENGTH_ECDSA_SIG..];
+
+        let attest_pub_key = v[..LENGTH_ECDSA_PUB_KEY]
+            .try_into()
+            .expect("parse attest_pub_key");
+        v = &v[LENGTH_ECDSA_PUB_KEY..];
+
+        // The remaining fields share the layout of certification data type 6.
+        let QeReportCertData {
+            qe_report,
+            qe_report_sig,
+            qe_auth_data,
+            certification_data,
+        } = QeReportCertData::try_from(v)?;
+
+        let out = Self {
+            sig,
+            attest_pub_key,
+            qe_report,
+            qe_report_sig,
+            qe_auth_data,
+            certification_data,
+        };
+
+        Ok(out)
+    }
+}
+
+impl PckIdentifier {
+    fn decode(data: &[u8], ppid_len: usize) -> Result<Self, String> {
+        let expected = ppid_len + 16 + 2 + 2;
+        if data.len() != expected {
+            let hint = format!("bad length: expect {}, got {}", expected, data.len());
+            return Err(hint);
+        }
+
+        let (ppid, v) = data.split_at(ppid_len);
+
+        let out = Self {
+            ppid: ppid.to_vec(),
+            cpu_svn: v[..16].try_into().expect("parse cpu_svn"),
+            pce_svn: u16::from_le_bytes([v[16], v[17]]),
+            pce_id: u16::from_le_bytes([v[18], v[19]]),
+        };
+
+        Ok(out)
+    }
+}
+
+impl Display for PckIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 13) };
+
+        writeln!(
+            f,
+            "{} = {}",
+            pad("ppid"),
+            hex::encode_to_string(self.ppid.as_ref())
+        )
+        .expect("write ppid");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("cpu_svn"),
+            hex::encode_to_string(self.cpu_svn.as_ref())
+        )
+        .expect("write cpu_svn");
+        writeln!(f, "{} = {:#06x}", pad("pce_svn"), self.pce_svn).expect("write pce_svn");
+        write!(f, "{} = {:#06x}", pad("pce_id"), self.pce_id).expect("write pce_id");
+
+        Ok(())
+    }
+}
+
+impl Display for QeAuthData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 4) };
+
+        writeln!(f, "{} = {}", pad("size"), self.size).expect("write size");
+        write!(
+            f,
+            "{} = {}",
+            pad("data"),
+            hex::encode_to_string(self.data.as_ref())
+        )
+        .expect("write data");
+
+        Ok(())
+    }
+}
+
+/// Parses the QE authentication data prefix of `value`, leaving the trailing bytes untouched.
+impl TryFrom<&[u8]> for QeAuthData {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), 2).map_err(|err| format!("validate length: {err}"))?;
+
+        let size = u16::from_le_bytes([value[0], value[1]]);
+        validate_minimum_length(value.len() - 2, size as usize)
+            .map_err(|err| format!("validate data length: {err}"))?;
+
+        let out = Self {
+            size,
+            data: value[2..(2 + size as usize)].to_vec(),
+        };
+
+        Ok(out)
+    }
+}
+
+impl Display for QeReportCertData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} = {}",
+            fixed_length_pad("qe_report_sig", 13),
+            hex::encode_to_string(self.qe_report_sig.as_ref())
+        )
+        .expect("write qe_report_sig");
+        writeln!(f).unwrap();
+
+        writeln!(f, "[sig.certification_data.qe_report]").unwrap();
+        writeln!(f, "{}", self.qe_report).expect("write qe_report");
+        writeln!(f).unwrap();
+
+        writeln!(f, "[sig.certification_data.qe_auth_data]").unwrap();
+        writeln!(f, "{}", self.qe_auth_data).expect("write qe_auth_data");
+        writeln!(f).unwrap();
+
+        writeln!(f, "[sig.certification_data.certification_data]").unwrap();
+        write!(f, "{}", self.certification_data).expect("write certification_data");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for QeReportCertData {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_REPORT_BODY + LENGTH_ECDSA_SIG)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let mut v = value;
+
+        let qe_report = ReportBody::try_from(v).map_err(|err| format!("parse qe_report: {err}"))?;
+        v = &v[LENGTH_REPORT_BODY..];
+
+        let qe_report_sig = v[..LENGTH_ECDSA_SIG]
+            .try_into()
+            .expect("parse qe_report_sig");
+        v = &v[LENGTH_ECDSA_SIG..];
+
+        let qe_auth_data =
+            QeAuthData::try_from(v).map_err(|err| format!("parse qe_auth_data: {err}"))?;
+        v = &v[(2 + qe_auth_data.data.len())..];
+
+        let certification_data = CertificationData::try_from(v)
+            .map_err(|err| format!("parse certification_data: {err}"))?;
+
+        let out = Self {
+            qe_report,
+            qe_report_sig,
+            qe_auth_data,
+            certification_data,
+        };
+
+        Ok(out)
+    }
+}
+
+/// Renders PEM text as a TOML multi-line string, falling back to hex for anything else.
+fn pem_or_hex(b: &[u8]) -> String {
+    match std::str::from_utf8(trim_nul(b)) {
+        Ok(s) if s.starts_with("-----BEGIN") => format!("'''\n{}'''", s),
+        _ => hex::encode_to_string(b),
+    }
+}
+
+/// Strips the NUL terminators which usually end the certification data produced by the QE.
+fn trim_nul(b: &[u8]) -> &[u8] {
+    match b.iter().rposition(|v| *v != 0) {
+        Some(i) => &b[..=i],
+        None => b,
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Display;
+
+use encoding::hex;
+
+use super::{fixed_length_pad, validate_minimum_length, ReportBody};
+use super::{LENGTH_QUOTE_HEADER, LENGTH_REPORT_BODY};
+
+pub const SIGN_TYPE_UNLINKABLE: u16 = 0;
+pub const SIGN_TYPE_LINKABLE: u16 = 1;
+
+/// EPID-based quote, i.e. `sgx_quote_t`.
+///
+/// Quotes embedded in IAS attestation verification reports are truncated right after the report
+/// body, in which case `signature_len` is 0 and `signature` is empty.
+///
+/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/sgx_quote.h#L89
+#[derive(Clone, Debug)]
+pub struct EpidQuote {
+    pub header: EpidQuoteHeader,
+    pub body: ReportBody,
+    pub signature_len: u32,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C, packed)]
+pub struct EpidQuoteHeader {
+    pub version: u16,
+    /// One of `SIGN_TYPE_UNLINKABLE` and `SIGN_TYPE_LINKABLE`.
+    pub sign_type: u16,
+    pub epid_group_id: [u8; 4],
+    pub qe_svn: u16,
+    pub pce_svn: u16,
+    pub xeid: u32,
+    pub basename: [u8; 32],
+}
+const _EPID_QUOTE_HEADER: [u8; LENGTH_QUOTE_HEADER] = [0u8; std::mem::size_of::<EpidQuoteHeader>()];
+
+impl Display for EpidQuote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[header]").unwrap();
+        writeln!(f, "{}", self.header).expect("write header");
+        writeln!(f).unwrap();
+
+        writeln!(f, "[body]").unwrap();
+        writeln!(f, "{}", self.body).expect("write body");
+        writeln!(f).unwrap();
+
+        let pad = |s: &str| -> String { fixed_length_pad(s, 13) };
+        writeln!(f, "[sig]").unwrap();
+        writeln!(f, "{} = {}", pad("signature_len"), self.signature_len)
+            .expect("write signature_len");
+        write!(
+            f,
+            "{} = {}",
+            pad("signature"),
+            hex::encode_to_string(&self.signature)
+        )
+        .expect("write signature");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for EpidQuote {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut v = value;
+
+        let header = EpidQuoteHeader::try_from(v).map_err(|err| format!("parse header: {err}"))?;
+        match header.version {
+            1 | 2 => {}
+            v => return Err(format!("unsupported version: {v}")),
+        }
+        v = &v[LENGTH_QUOTE_HEADER..];
+
+        let body = ReportBody::try_from(v).map_err(|err| format!("parse report body: {err}"))?;
+        v = &v[LENGTH_REPORT_BODY..];
+
+        if v.is_empty() {
+            let out = Self {
+                header,
+                body,
+                signature_len: 0,
+                signature: vec![],
+            };
+            return Ok(out);
+        }
+
+        validate_minimum_length(v.len(), 4)
+            .map_err(|err| format!("validate signature_len: {err}"))?;
+        let signature_len = u32::from_le_bytes((&v[..4]).try_into().expect("parse signature_len"));
+        v = &v[4..];
+        if v.len() != (signature_len as usize) {
+            let hint = format!(
+                "bad signature length: expect {}, got {}",
+                signature_len,
+                v.len()
+            );
+            return Err(hint);
+        }
+
+        let out = Self {
+            header,
+            body,
+            signature_len,
+            signature: v.to_vec(),
+        };
+
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for EpidQuoteHeader {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_QUOTE_HEADER) }
+    }
+}
+
+impl Display for EpidQuoteHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 13) };
+
+        let Self {
+            version,
+            sign_type,
+            qe_svn,
+            pce_svn,
+            xeid,
+            ..
+        } = *self;
+
+        let sign_type_name = match sign_type {
+            SIGN_TYPE_UNLINKABLE => "UNLINKABLE",
+            SIGN_TYPE_LINKABLE => "LINKABLE",
+            _ => "UNKNOWN",
+        };
+
+        writeln!(f, "{} = {:#06x}", pad("version"), version).expect("write version");
+        writeln!(
+            f,
+            "{} = {} # {}",
+            pad("sign_type"),
+            sign_type,
+            sign_type_name
+        )
+        .expect("write sign_type");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("epid_group_id"),
+            hex::encode_to_string(self.epid_group_id.as_ref())
+        )
+        .expect("write epid_group_id");
+        writeln!(f, "{} = {:#06x}", pad("qe_svn"), qe_svn).expect("write qe_svn");
+        writeln!(f, "{} = {:#06x}", pad("pce_svn"), pce_svn).expect("write pce_svn");
+        writeln!(f, "{} = {:#010x}", pad("xeid"), xeid).expect("write xeid");
+        write!(
+            f,
+            "{} = {}",
+            pad("basename"),
+            hex::encode_to_string(self.basename.as_ref())
+        )
+        .expect("write basename");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for EpidQuoteHeader {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_QUOTE_HEADER)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let out = unsafe { *(value.as_ptr() as *const Self) };
+
+        Ok(out)
+    }
+}
@@ -1,64 +1,163 @@
 use std::fmt::Display;
 
 use encoding::hex;
+use openssl::sha;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 
 const LENGTH_REPORT_BODY: usize = 384;
 const LENGTH_QUOTE_HEADER: usize = 48;
+const LENGTH_QUOTE_BODY_DESCRIPTOR: usize = 6;
 const LENGTH_SIG_STRUCT: usize = 1808;
-
-#[derive(Clone, Copy, Debug, Default)]
-#[repr(C, packed)]
-pub struct Attributes {
-    pub flags: u64,
-    pub xfrm: u64,
-}
+const LENGTH_SIG_STRUCT_HEADER: usize = 128;
+const LENGTH_SIG_STRUCT_BODY: usize = 128;
+const LENGTH_REPORT: usize = 432;
+const LENGTH_TARGET_INFO: usize = 512;
+
+pub const TEE_TYPE_SGX: u32 = 0x00000000;
+pub const TEE_TYPE_TDX: u32 = 0x00000081;
+
+pub const BODY_TYPE_SGX: u16 = 1;
+pub const BODY_TYPE_TD10: u16 = 2;
+pub const BODY_TYPE_TD15: u16 = 3;
+
+/// Bit of `SigStructHeader::type_` marking the enclave as a debug one, while bits 0 to 30 must be
+/// zero.
+///
+/// ref: `type` of `css_header_t` at https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/internal/arch.h#L218
+pub const SIG_STRUCT_TYPE_DEBUG: u32 = 1 << 31;
+
+/// Fixed values of `SigStructHeader::header` and `SigStructHeader::header2` required by EINIT.
+pub const SIG_STRUCT_HEADER: [u8; 12] = [
+    0x06, 0x00, 0x00, 0x00, 0xe1, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+];
+pub const SIG_STRUCT_HEADER2: [u8; 16] = [
+    0x01, 0x01, 0x00, 0x00, 0x60, 0x00, 0x00, 0x00, 0x60, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+];
 
 /// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.14/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_3.h#L177
-#[derive(Clone, Debug, Default)]
+///
+/// Quotes of version 4 are supported as well, whose body is either an SGX report body or a TDX TD
+/// report body depending on the TEE type. Since version 5, the body type is given explicitly by a
+/// body descriptor preceding the body.
+///
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.16/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_4.h#L140
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.19/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_5.h#L77
+#[derive(Clone, Debug, Serialize)]
 pub struct Quote3 {
     pub header: QuoteHeader,
-    pub body: ReportBody,
-    pub signature: Vec<u8>,
+    /// Only present since version 5.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_descriptor: Option<QuoteBodyDescriptor>,
+    pub body: QuoteBody,
+    pub signature_data_len: u32,
+    pub signature: EcdsaSigData,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(untagged)]
+pub enum QuoteBody {
+    Sgx(ReportBody),
+    Td10(TdReportBody),
+    Td15(TdReportBody15),
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[repr(C, packed)]
+pub struct QuoteBodyDescriptor {
+    /// One of `BODY_TYPE_SGX`, `BODY_TYPE_TD10` and `BODY_TYPE_TD15`.
+    pub body_type: u16,
+    pub size: u32,
+}
+const _QUOTE_BODY_DESCRIPTOR: [u8; LENGTH_QUOTE_BODY_DESCRIPTOR] =
+    [0u8; std::mem::size_of::<QuoteBodyDescriptor>()];
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
 #[repr(C, packed)]
 pub struct QuoteHeader {
     pub version: u16,
     pub att_key_type: u16,
+    /// TEE type since version 4, i.e. one of `TEE_TYPE_SGX` and `TEE_TYPE_TDX`.
     pub att_key_data_0: u32,
+    /// Reserved since version 4.
     pub qe_svn: u16,
+    /// Reserved since version 4.
     pub pce_svn: u16,
+    #[serde(serialize_with = "ser::hex")]
     pub vendor_id: [u8; 16],
+    #[serde(serialize_with = "ser::hex")]
     pub user_data: [u8; 20],
 }
 const _QUOTE_HEADER: [u8; LENGTH_QUOTE_HEADER] = [0u8; std::mem::size_of::<QuoteHeader>()];
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize)]
 #[repr(C, packed)]
 pub struct ReportBody {
+    #[serde(serialize_with = "ser::hex")]
     pub cpu_svn: [u8; 16],
-    pub misc_select: u32,
+    pub misc_select: MiscSelect,
+    #[serde(serialize_with = "ser::hex")]
     pub reserved1: [u8; 12],
+    #[serde(serialize_with = "ser::hex")]
     pub isv_ext_prod_id: [u8; 16],
     pub attributes: Attributes,
+    #[serde(serialize_with = "ser::hex")]
     pub mr_enclave: [u8; 32],
+    #[serde(serialize_with = "ser::hex")]
     pub reserved2: [u8; 32],
+    #[serde(serialize_with = "ser::hex")]
     pub mr_signer: [u8; 32],
+    #[serde(serialize_with = "ser::hex")]
     pub reserved3: [u8; 32],
+    #[serde(serialize_with = "ser::hex")]
     pub config_id: [u8; 64],
     pub isv_prod_id: u16,
     pub isv_svn: u16,
     pub config_svn: u16,
+    #[serde(serialize_with = "ser::hex")]
     pub reserved4: [u8; 42],
+    #[serde(serialize_with = "ser::hex")]
     pub isv_family_id: [u8; 16],
+    #[serde(serialize_with = "ser::hex")]
     pub report_data: [u8; 64],
 }
 const _REPORT_BODY: [u8; LENGTH_REPORT_BODY] = [0u8; std::mem::size_of::<ReportBody>()];
 
-/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/internal/arch.h#L258
+/// REPORT produced by EREPORT, i.e. `sgx_report_t`. The MAC is an AES-128-CMAC over the body with
+/// the report key of the target enclave.
+///
+/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/sgx_report.h#L119
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(C, packed)]
+pub struct Report {
+    pub body: ReportBody,
+    pub key_id: [u8; 32],
+    pub mac: [u8; 16],
+}
+const _REPORT: [u8; LENGTH_REPORT] = [0u8; std::mem::size_of::<Report>()];
+
+/// TARGETINFO consumed by EREPORT to produce a REPORT for the target enclave, i.e.
+/// `sgx_target_info_t`.
+///
+/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/sgx_report.h#L81
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct TargetInfo {
+    pub mr_enclave: [u8; 32],
+    pub attributes: Attributes,
+    pub cet_attributes: u8,
+    pub reserved1: u8,
+    pub config_svn: u16,
+    pub misc_select: MiscSelect,
+    pub reserved2: [u8; 8],
+    pub config_id: [u8; 64],
+    pub reserved3: [u8; 384],
+}
+const _TARGET_INFO: [u8; LENGTH_TARGET_INFO] = [0u8; std::mem::size_of::<TargetInfo>()];
+
+/// ref: https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/internal/arch.h#L258
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[repr(C, packed)]
 pub struct SigStruct {
     pub header: SigStructHeader,
     pub key: SigStructKey,
@@ -68,48 +167,63 @@ pub struct SigStruct {
 const _SIG_STRUCT: [u8; LENGTH_SIG_STRUCT] = [0u8; std::mem::size_of::<SigStruct>()];
 
 /// https://github.com/intel/linux-sgx/blob/sgx_2.18/common/inc/internal/arch.h#L236
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize)]
 #[repr(C, packed)]
 pub struct SigStructBody {
-    pub misc_select: u32,
-    pub misc_mask: u32,
+    pub misc_select: MiscSelect,
+    pub misc_mask: MiscSelect,
+    #[serde(serialize_with = "ser::hex")]
     pub reserved: [u8; 4],
+    #[serde(serialize_with = "ser::hex")]
     pub isv_family_id: [u8; 16],
     pub attributes: Attributes,
     pub attribute_mask: Attributes, /* (944) Mask of Attributes to Enforce */
-    pub enclave_hash: [u8; 32],     /* (960) MRENCLAVE - (32 bytes) */
-    pub reserved2: [u8; 16],        /* (992) Must be 0 */
-    pub isvext_prod_id: [u8; 16],   /* (1008) ISV assigned Extended Product ID */
+    #[serde(serialize_with = "ser::hex")]
+    pub enclave_hash: [u8; 32], /* (960) MRENCLAVE - (32 bytes) */
+    #[serde(serialize_with = "ser::hex")]
+    pub reserved2: [u8; 16], /* (992) Must be 0 */
+    #[serde(serialize_with = "ser::hex")]
+    pub isvext_prod_id: [u8; 16], /* (1008) ISV assigned Extended Product ID */
     pub isv_prod_id: u16,           /* (1024) ISV assigned Product ID */
     pub isv_svn: u16,               /* (1026) ISV assigned SVN */
 }
-const _SIG_STRUCT_BODY: [u8; 128] = [0; std::mem::size_of::<SigStructBody>()];
+const _SIG_STRUCT_BODY: [u8; LENGTH_SIG_STRUCT_BODY] = [0; std::mem::size_of::<SigStructBody>()];
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize)]
 #[repr(C, packed)]
 pub struct SigStructBuffer {
+    #[serde(serialize_with = "ser::hex")]
     pub reserved: [u8; 12],
+    #[serde(serialize_with = "ser::hex")]
     pub q1: [u8; 384],
+    #[serde(serialize_with = "ser::hex")]
     pub q2: [u8; 384],
 }
 const _SIG_STRUCT_BUF: [u8; 780] = [0; std::mem::size_of::<SigStructBuffer>()];
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize)]
 #[repr(C, packed)]
 pub struct SigStructHeader {
+    #[serde(serialize_with = "ser::hex")]
     pub header: [u8; 12],
+    #[serde(rename = "type")]
     pub type_: u32,
     pub module_vendor: u32,
     pub date: u32,
+    #[serde(serialize_with = "ser::hex")]
     pub header2: [u8; 16],
     pub hw_version: u32,
+    #[serde(serialize_with = "ser::hex")]
     pub reserved: [u8; 84],
 }
-const _SIG_STRUCT_HEADER: [u8; 128] = [0; std::mem::size_of::<SigStructHeader>()];
+const _SIG_STRUCT_HEADER: [u8; LENGTH_SIG_STRUCT_HEADER] =
+    [0; std::mem::size_of::<SigStructHeader>()];
 
+/// Serialized along with the MRSIGNER derived from the modulus.
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct SigStructKey {
+    /// Little-endian modulus of the RSA-3072 signing key.
     pub modulus: [u8; 384],
     pub exponent: [u8; 4],
     pub signature: [u8; 384],
@@ -139,26 +253,26 @@ impl Default for ReportBody {
     }
 }
 
-impl Display for Attributes {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (flags, xfrm) = (self.flags, self.xfrm);
-        write!(f, "flags={:#066b}, xfrm={:#066b}", flags, xfrm)
-    }
-}
-
 impl Display for Quote3 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "[header]").unwrap();
         writeln!(f, "{}", self.header).expect("write header");
         writeln!(f, "").unwrap();
 
+        if let Some(v) = &self.body_descriptor {
+            writeln!(f, "[body_descriptor]").unwrap();
+            writeln!(f, "{v}").expect("write body_descriptor");
+            writeln!(f).unwrap();
+        }
+
         writeln!(f, "[body]").unwrap();
         writeln!(f, "{}", self.body).expect("write body");
         writeln!(f, "").unwrap();
 
         writeln!(f, "[sig]").unwrap();
-        writeln!(f, "length = {}", self.signature.len()).expect("write signature_length");
-        writeln!(f, "data   = {}", hex::encode_to_string(self.signature.as_ref())).expect("write signature data");
+        let length = fixed_length_pad("length", 14);
+        writeln!(f, "{length} = {}", self.signature_data_len).expect("write signature_length");
+        write!(f, "{}", self.signature).expect("write signature data");
 
         Ok(())
     }
@@ -173,21 +287,60 @@ impl TryFrom<&[u8]> for Quote3 {
         let header = QuoteHeader::try_from(v).map_err(|err| format!("parse header: {err}"))?;
         v = &v[LENGTH_QUOTE_HEADER..];
 
-        let body = ReportBody::try_from(v).map_err(|err| format!("parse report body: {err}"))?;
-        v = &v[LENGTH_REPORT_BODY..];
+        let (version, tee_type) = (header.version, header.att_key_data_0);
+
+        let body_descriptor = if version >= 5 {
+            let d = QuoteBodyDescriptor::try_from(v)
+                .map_err(|err| format!("parse body descriptor: {err}"))?;
+            v = &v[LENGTH_QUOTE_BODY_DESCRIPTOR..];
+            Some(d)
+        } else {
+            None
+        };
 
-        let sig_len = u32::from_le_bytes((&v[..4]).try_into().expect("parse signature_length"));
+        let body = match (version, tee_type) {
+            (3 | 4, TEE_TYPE_SGX) => ReportBody::try_from(v)
+                .map(QuoteBody::Sgx)
+                .map_err(|err| format!("parse report body: {err}"))?,
+            (4, TEE_TYPE_TDX) => TdReportBody::try_from(v)
+                .map(QuoteBody::Td10)
+                .map_err(|err| format!("parse TD report body: {err}"))?,
+            (3 | 4, _) => return Err(format!("unsupported TEE type: {tee_type:#010x}")),
+            (5, _) => {
+                let d = body_descriptor.expect("body descriptor of version 5");
+                QuoteBody::decode(d.body_type, v, d.size as usize)
+                    .map_err(|err| format!("parse body: {err}"))?
+            }
+            _ => return Err(format!("unsupported version: {version}")),
+        };
+        v = &v[body.as_ref().len()..];
+
+        validate_minimum_length(v.len(), 4)
+            .map_err(|err| format!("validate signature_length: {err}"))?;
+        let signature_data_len =
+            u32::from_le_bytes((&v[..4]).try_into().expect("parse signature_length"));
         v = &v[4..];
-        if v.len() != (sig_len as usize) {
-            let hint = format!("bad sig data length: expect {}, got {}", sig_len, v.len());
+        if v.len() != (signature_data_len as usize) {
+            let hint = format!(
+                "bad sig data length: expect {}, got {}",
+                signature_data_len,
+                v.len()
+            );
             return Err(hint);
         }
 
-        let signature = v.to_vec();
+        let signature = if version == 3 {
+            EcdsaSigData::try_from(v)
+        } else {
+            EcdsaSigData::decode_v4(v)
+        }
+        .map_err(|err| format!("parse signature data: {err}"))?;
 
         let out = Self {
             header,
+            body_descriptor,
             body,
+            signature_data_len,
             signature,
         };
 
@@ -195,6 +348,104 @@ impl TryFrom<&[u8]> for Quote3 {
     }
 }
 
+impl QuoteBody {
+    /// Decodes the body of version 5 quotes according to the body type and size specified by the
+    /// body descriptor.
+    pub fn decode(body_type: u16, b: &[u8], size: usize) -> Result<Self, String> {
+        let expected_size = match body_type {
+            BODY_TYPE_SGX => LENGTH_REPORT_BODY,
+            BODY_TYPE_TD10 => LENGTH_TD_REPORT_BODY,
+            BODY_TYPE_TD15 => LENGTH_TD_REPORT_BODY_15,
+            _ => return Err(format!("unsupported body type: {body_type}")),
+        };
+        if size != expected_size {
+            let hint = format!(
+                "bad size for {}: expect {expected_size}, got {size}",
+                body_type_name(body_type)
+            );
+            return Err(hint);
+        }
+
+        let out = match body_type {
+            BODY_TYPE_SGX => Self::Sgx(ReportBody::try_from(b)?),
+            BODY_TYPE_TD10 => Self::Td10(TdReportBody::try_from(b)?),
+            _ => Self::Td15(TdReportBody15::try_from(b)?),
+        };
+
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for QuoteBody {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Sgx(v) => v.as_ref(),
+            Self::Td10(v) => v.as_ref(),
+            Self::Td15(v) => v.as_ref(),
+        }
+    }
+}
+
+impl Display for QuoteBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sgx(v) => write!(f, "{v}"),
+            Self::Td10(v) => write!(f, "{v}"),
+            Self::Td15(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl AsRef<[u8]> for QuoteBodyDescriptor {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                LENGTH_QUOTE_BODY_DESCRIPTOR,
+            )
+        }
+    }
+}
+
+impl Display for QuoteBodyDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 9) };
+
+        let Self { body_type, size } = *self;
+
+        writeln!(
+            f,
+            "{} = {} # {}",
+            pad("body_type"),
+            body_type,
+            body_type_name(body_type)
+        )
+        .expect("write body_type");
+        write!(f, "{} = {}", pad("size"), size).expect("write size");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for QuoteBodyDescriptor {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_QUOTE_BODY_DESCRIPTOR)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let out = unsafe { *(value.as_ptr() as *const Self) };
+
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for QuoteHeader {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_QUOTE_HEADER) }
+    }
+}
+
 impl Display for QuoteHeader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pad = |s: &str| -> String { fixed_length_pad(s, 14) };
@@ -211,10 +462,14 @@ impl Display for QuoteHeader {
         writeln!(f, "{} = {:#06x}", pad("version"), version).expect("write version");
         writeln!(f, "{} = {:#06x}", pad("attn_key_type"), att_key_type)
             .expect("write attn_key_type");
-        writeln!(f, "{} = {:#010x}", pad("attn_key_data0"), att_key_data_0)
-            .expect("write attn_key_data0");
-        writeln!(f, "{} = {:#06x}", pad("qe_svn"), qe_svn).expect("write qe_svn");
-        writeln!(f, "{} = {:#06x}", pad("pce_svn"), pce_svn).expect("write pce_svn");
+        if version < 4 {
+            writeln!(f, "{} = {:#010x}", pad("attn_key_data0"), att_key_data_0)
+                .expect("write attn_key_data0");
+            writeln!(f, "{} = {:#06x}", pad("qe_svn"), qe_svn).expect("write qe_svn");
+            writeln!(f, "{} = {:#06x}", pad("pce_svn"), pce_svn).expect("write pce_svn");
+        } else {
+            writeln!(f, "{} = {:#010x}", pad("tee_type"), att_key_data_0).expect("write tee_type");
+        }
 
         writeln!(
             f,
@@ -248,6 +503,160 @@ impl TryFrom<&[u8]> for QuoteHeader {
     }
 }
 
+impl AsRef<[u8]> for Report {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_REPORT) }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 6) };
+
+        writeln!(
+            f,
+            "{} = {}",
+            pad("key_id"),
+            hex::encode_to_string(self.key_id.as_ref())
+        )
+        .expect("write key_id");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("mac"),
+            hex::encode_to_string(self.mac.as_ref())
+        )
+        .expect("write mac");
+        writeln!(f).unwrap();
+
+        writeln!(f, "[body]").unwrap();
+        write!(f, "{}", self.body).expect("write body");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for Report {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_REPORT)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let out = unsafe { *(value.as_ptr() as *const Self) };
+
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for TargetInfo {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_TARGET_INFO) }
+    }
+}
+
+impl Default for TargetInfo {
+    fn default() -> Self {
+        Self {
+            mr_enclave: Default::default(),
+            attributes: Default::default(),
+            cet_attributes: Default::default(),
+            reserved1: Default::default(),
+            config_svn: Default::default(),
+            misc_select: Default::default(),
+            reserved2: Default::default(),
+            config_id: [0u8; 64],
+            reserved3: [0u8; 384],
+        }
+    }
+}
+
+impl Display for TargetInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 14) };
+
+        let Self {
+            attributes,
+            cet_attributes,
+            config_svn,
+            misc_select,
+            ..
+        } = *self;
+
+        writeln!(
+            f,
+            "{} = {}",
+            pad("mr_enclave"),
+            hex::encode_to_string(self.mr_enclave.as_ref())
+        )
+        .expect("write mr_enclave");
+        writeln!(f, "{} = {}", pad("attributes"), attributes).expect("write attributes");
+        writeln!(f, "{} = {:#010b}", pad("cet_attributes"), cet_attributes)
+            .expect("write cet_attributes");
+        writeln!(f, "{} = {:#06x}", pad("config_svn"), config_svn).expect("write config_svn");
+        writeln!(f, "{} = {}", pad("misc_select"), misc_select).expect("write misc_select");
+        write!(
+            f,
+            "{} = {}",
+            pad("config_id"),
+            hex::encode_to_string(self.config_id.as_ref())
+        )
+        .expect("write config_id");
+
+        Ok(())
+    }
+}
+
+/// Targets the enclave which the report body is produced by.
+impl From<&ReportBody> for TargetInfo {
+    fn from(v: &ReportBody) -> Self {
+        Self {
+            mr_enclave: v.mr_enclave,
+            attributes: v.attributes,
+            config_svn: v.config_svn,
+            misc_select: v.misc_select,
+            config_id: v.config_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// Targets the enclave signed by the SIGSTRUCT, assuming no CONFIGID or CONFIGSVN is specified
+/// when the enclave is initialized. The INIT flag is set as the enclave has been initialized by
+/// the time it's targeted.
+impl From<&SigStruct> for TargetInfo {
+    fn from(v: &SigStruct) -> Self {
+        let mut attributes = v.body.attributes;
+        attributes.flags |= 0x01;
+
+        Self {
+            mr_enclave: v.body.enclave_hash,
+            attributes,
+            misc_select: v.body.misc_select,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for TargetInfo {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_TARGET_INFO)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let out = unsafe { *(value.as_ptr() as *const Self) };
+
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for ReportBody {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_REPORT_BODY) }
+    }
+}
+
 impl Display for ReportBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pad = |s: &str| -> String { fixed_length_pad(s, 15) };
@@ -269,7 +678,7 @@ impl Display for ReportBody {
             ..
         } = *self;
 
-        writeln!(f, "{} = {:#034b}", pad("misc_select"), misc_select).expect("write misc_select");
+        writeln!(f, "{} = {}", pad("misc_select"), misc_select).expect("write misc_select");
 
         writeln!(
             f,
@@ -373,6 +782,22 @@ impl TryFrom<&[u8]> for ReportBody {
     }
 }
 
+impl AsRef<[u8]> for SigStruct {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_SIG_STRUCT) }
+    }
+}
+
+impl SigStruct {
+    /// The message signed by the key, i.e. the header followed by the body.
+    pub fn signed_message(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(LENGTH_SIG_STRUCT_HEADER + LENGTH_SIG_STRUCT_BODY);
+        out.extend_from_slice(self.header.as_ref());
+        out.extend_from_slice(self.body.as_ref());
+        out
+    }
+}
+
 impl Display for SigStruct {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "[header]").unwrap();
@@ -410,7 +835,8 @@ impl TryFrom<&[u8]> for SigStruct {
             std::slice::from_raw_parts_mut(&mut out as *mut Self as *mut u8, LENGTH_SIG_STRUCT)
         };
 
-        // todo: validate
+        // Contents are left unchecked so that corrupted SIGSTRUCTs can still be dumped, whose
+        // validity is checked by `verify-sig-struct` instead.
 
         buf.copy_from_slice(value);
 
@@ -474,6 +900,14 @@ impl Default for SigStructHeader {
     }
 }
 
+impl AsRef<[u8]> for SigStructHeader {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_SIG_STRUCT_HEADER)
+        }
+    }
+}
+
 impl Display for SigStructHeader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pad = |s: &str| -> String { fixed_length_pad(s, 16) };
@@ -486,7 +920,7 @@ impl Display for SigStructHeader {
         )
         .expect("write header");
 
-        let type_desc = if (self.type_ & 0x01) == 0x01 {
+        let type_desc = if (self.type_ & SIG_STRUCT_TYPE_DEBUG) != 0 {
             "debug"
         } else {
             "prod"
@@ -544,6 +978,14 @@ impl Default for SigStructKey {
     }
 }
 
+impl SigStructKey {
+    /// MRSIGNER of enclaves signed by this key, i.e. the SHA-256 digest of the little-endian
+    /// modulus.
+    pub fn mr_signer(&self) -> [u8; 32] {
+        sha::sha256(self.modulus.as_ref())
+    }
+}
+
 impl Display for SigStructKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pad = |s: &str| -> String { fixed_length_pad(s, 9) };
@@ -572,19 +1014,57 @@ impl Display for SigStructKey {
         )
         .expect("write signature");
 
+        writeln!(
+            f,
+            "{} = {}",
+            pad("mr_signer"),
+            hex::encode_to_string(self.mr_signer().as_ref())
+        )
+        .expect("write mr_signer");
+
         Ok(())
     }
 }
 
+impl Serialize for SigStructKey {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut out = s.serialize_struct("SigStructKey", 4)?;
+        out.serialize_field("modulus", &hex::encode_to_string(self.modulus.as_ref()))?;
+        out.serialize_field("exponent", &u32::from_le_bytes(self.exponent))?;
+        out.serialize_field("signature", &hex::encode_to_string(self.signature.as_ref()))?;
+        out.serialize_field(
+            "mr_signer",
+            &hex::encode_to_string(self.mr_signer().as_ref()),
+        )?;
+        out.end()
+    }
+}
+
+impl AsRef<[u8]> for SigStructBody {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_SIG_STRUCT_BODY)
+        }
+    }
+}
+
 impl Display for SigStructBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pad = |s: &str| -> String { fixed_length_pad(s, 14) };
 
-        let v = self.misc_select;
-        writeln!(f, "{} = {:#034b}", pad("misc_select"), v).expect("write misc_select");
-
-        let v = self.misc_mask;
-        writeln!(f, "{} = {:#034b}", pad("misc_mask"), v).expect("write misc_mask");
+        let (misc_select, misc_mask) = (self.misc_select, self.misc_mask);
+        writeln!(f, "{} = {}", pad("misc_select"), misc_select).expect("write misc_select");
+        writeln!(f, "{} = {}", pad("misc_mask"), misc_mask).expect("write misc_mask");
+        writeln!(
+            f,
+            "{} = [{}]",
+            pad("misc_enforced"),
+            misc_select.enforced_by(misc_mask).join(", ")
+        )
+        .expect("write misc_enforced");
 
         writeln!(
             f,
@@ -640,6 +1120,15 @@ impl Display for SigStructBody {
     }
 }
 
+fn body_type_name(body_type: u16) -> &'static str {
+    match body_type {
+        BODY_TYPE_SGX => "SGX_REPORT_BODY",
+        BODY_TYPE_TD10 => "TD_REPORT_BODY_10",
+        BODY_TYPE_TD15 => "TD_REPORT_BODY_15",
+        _ => "UNKNOWN",
+    }
+}
+
 fn fixed_length_pad(s: &str, n: usize) -> String {
     if s.len() >= n {
         return s.to_string();
@@ -663,6 +1152,20 @@ fn validate_minimum_length(got: usize, minimum: usize) -> Result<(), String> {
     }
 }
 
+mod attributes;
 mod checker;
-
+mod der;
+mod ecdsa;
+mod epid;
+mod measure;
+mod pck;
+mod ser;
+mod tdx;
+
+pub use attributes::*;
 pub use checker::*;
+pub use ecdsa::*;
+pub use epid::*;
+pub use measure::*;
+pub use pck::*;
+pub use tdx::*;
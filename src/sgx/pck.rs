@@ -0,0 +1,328 @@
+use std::fmt::Display;
+
+use encoding::hex;
+
+use super::der::{self, Tlv};
+use super::fixed_length_pad;
+
+/// DER encoding of the OID 1.2.840.113741.1.13.1, i.e. the SGX extensions of PCK certificates.
+const OID_SGX_EXTENSIONS: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+
+const ARC_PPID: u8 = 1;
+const ARC_TCB: u8 = 2;
+const ARC_PCE_ID: u8 = 3;
+const ARC_FMSPC: u8 = 4;
+const ARC_SGX_TYPE: u8 = 5;
+const ARC_PLATFORM_INSTANCE_ID: u8 = 6;
+const ARC_CONFIGURATION: u8 = 7;
+
+const ARC_CONFIGURATION_DYNAMIC_PLATFORM: u8 = 1;
+const ARC_CONFIGURATION_CACHED_KEYS: u8 = 2;
+const ARC_CONFIGURATION_SMT_ENABLED: u8 = 3;
+
+const ARC_TCB_PCESVN: u8 = 17;
+const ARC_TCB_CPUSVN: u8 = 18;
+
+/// Configuration of a multi-package platform, only present in PCK certificates issued by the
+/// Platform CA.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlatformConfiguration {
+    pub dynamic_platform: Option<bool>,
+    pub cached_keys: Option<bool>,
+    pub smt_enabled: Option<bool>,
+}
+
+/// TCB of the platform which a PCK certificate is issued to.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PckTcb {
+    /// SGX TCB component SVNs, i.e. `sgxTcbComp01Svn` to `sgxTcbComp16Svn`.
+    pub components: [u8; 16],
+    pub pce_svn: u16,
+    pub cpu_svn: [u8; 16],
+}
+
+/// SGX extensions of a PCK certificate.
+///
+/// ref: https://api.trustedservices.intel.com/documents/Intel_SGX_PCK_Certificate_CRL_Spec-1.4.pdf
+#[derive(Clone, Debug, Default)]
+pub struct SgxExtensions {
+    pub ppid: [u8; 16],
+    pub tcb: PckTcb,
+    pub pce_id: [u8; 2],
+    pub fmspc: [u8; 6],
+    pub sgx_type: SgxType,
+    /// Only present in PCK certificates issued by the Platform CA.
+    pub platform_instance_id: Option<[u8; 16]>,
+    pub configuration: Option<PlatformConfiguration>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SgxType {
+    #[default]
+    Standard,
+    Scalable,
+    ScalableWithIntegrity,
+}
+
+impl PlatformConfiguration {
+    fn from_tlv(seq: &Tlv) -> Result<Self, String> {
+        let mut out = Self::default();
+
+        for (arc, v) in sgx_extension_items(seq, &[ARC_CONFIGURATION])? {
+            let flag = match arc {
+                ARC_CONFIGURATION_DYNAMIC_PLATFORM => &mut out.dynamic_platform,
+                ARC_CONFIGURATION_CACHED_KEYS => &mut out.cached_keys,
+                ARC_CONFIGURATION_SMT_ENABLED => &mut out.smt_enabled,
+                _ => continue,
+            };
+            *flag = Some(
+                v.as_bool()
+                    .map_err(|err| format!("decode flag #{arc}: {err}"))?,
+            );
+        }
+
+        Ok(out)
+    }
+}
+
+impl Display for PlatformConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 16) };
+        let show = |v: Option<bool>| -> String {
+            v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+        };
+
+        writeln!(
+            f,
+            "{} = {}",
+            pad("dynamic_platform"),
+            show(self.dynamic_platform)
+        )
+        .expect("write dynamic_platform");
+        writeln!(f, "{} = {}", pad("cached_keys"), show(self.cached_keys))
+            .expect("write cached_keys");
+        write!(f, "{} = {}", pad("smt_enabled"), show(self.smt_enabled))
+            .expect("write smt_enabled");
+
+        Ok(())
+    }
+}
+
+impl Display for PckTcb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 10) };
+
+        let components = self
+            .components
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(f, "{} = [{components}]", pad("components")).expect("write components");
+        writeln!(f, "{} = {}", pad("pce_svn"), self.pce_svn).expect("write pce_svn");
+        write!(
+            f,
+            "{} = {}",
+            pad("cpu_svn"),
+            hex::encode_to_string(self.cpu_svn.as_ref())
+        )
+        .expect("write cpu_svn");
+
+        Ok(())
+    }
+}
+
+impl PckTcb {
+    fn from_tlv(seq: &Tlv) -> Result<Self, String> {
+        let mut out = Self::default();
+
+        for (arc, v) in sgx_extension_items(seq, &[ARC_TCB])? {
+            match arc {
+                1..=16 => {
+                    let svn = v
+                        .as_u64()
+                        .map_err(|err| format!("decode comp{arc:02}: {err}"))?;
+                    out.components[(arc - 1) as usize] = u8::try_from(svn)
+                        .map_err(|_| format!("comp{arc:02} out of range: {svn}"))?;
+                }
+                ARC_TCB_PCESVN => {
+                    let svn = v.as_u64().map_err(|err| format!("decode pcesvn: {err}"))?;
+                    out.pce_svn =
+                        u16::try_from(svn).map_err(|_| format!("pcesvn out of range: {svn}"))?;
+                }
+                ARC_TCB_CPUSVN => {
+                    out.cpu_svn =
+                        fixed_octet_string(&v).map_err(|err| format!("decode cpusvn: {err}"))?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl SgxExtensions {
+    /// Extracts the SGX extensions from a DER-encoded PCK certificate.
+    pub fn from_cert_der(cert: &[u8]) -> Result<Self, String> {
+        let v = der::find_extension(cert, OID_SGX_EXTENSIONS)
+            .map_err(|err| format!("find extension: {err}"))?
+            .ok_or_else(|| "no SGX extensions".to_string())?;
+
+        Self::try_from(v)
+    }
+}
+
+impl Display for SgxExtensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 20) };
+
+        writeln!(
+            f,
+            "{} = {}",
+            pad("ppid"),
+            hex::encode_to_string(self.ppid.as_ref())
+        )
+        .expect("write ppid");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("pce_id"),
+            hex::encode_to_string(self.pce_id.as_ref())
+        )
+        .expect("write pce_id");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("fmspc"),
+            hex::encode_to_string(self.fmspc.as_ref())
+        )
+        .expect("write fmspc");
+        writeln!(f, "{} = {}", pad("sgx_type"), self.sgx_type).expect("write sgx_type");
+        if let Some(v) = self.platform_instance_id {
+            writeln!(
+                f,
+                "{} = {}",
+                pad("platform_instance_id"),
+                hex::encode_to_string(v.as_ref())
+            )
+            .expect("write platform_instance_id");
+        }
+        writeln!(f).unwrap();
+
+        writeln!(f, "[tcb]").unwrap();
+        write!(f, "{}", self.tcb).expect("write tcb");
+
+        if let Some(v) = &self.configuration {
+            writeln!(f).unwrap();
+            writeln!(f).unwrap();
+            writeln!(f, "[configuration]").unwrap();
+            write!(f, "{v}").expect("write configuration");
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the value of the SGX extensions, i.e. a DER-encoded SEQUENCE.
+impl TryFrom<&[u8]> for SgxExtensions {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (seq, _) = Tlv::read_tagged(value, der::TAG_SEQUENCE)?;
+
+        let mut out = Self::default();
+        for (arc, v) in sgx_extension_items(&seq, &[])? {
+            match arc {
+                ARC_PPID => {
+                    out.ppid = fixed_octet_string(&v).map_err(|err| format!("parse PPID: {err}"))?
+                }
+                ARC_TCB => {
+                    out.tcb = PckTcb::from_tlv(&v).map_err(|err| format!("parse TCB: {err}"))?
+                }
+                ARC_PCE_ID => {
+                    out.pce_id =
+                        fixed_octet_string(&v).map_err(|err| format!("parse PCE-ID: {err}"))?
+                }
+                ARC_FMSPC => {
+                    out.fmspc =
+                        fixed_octet_string(&v).map_err(|err| format!("parse FMSPC: {err}"))?
+                }
+                ARC_SGX_TYPE => {
+                    out.sgx_type = match v.as_u64() {
+                        Ok(0) => SgxType::Standard,
+                        Ok(1) => SgxType::Scalable,
+                        Ok(2) => SgxType::ScalableWithIntegrity,
+                        Ok(v) => return Err(format!("unknown SGX type: {v}")),
+                        Err(err) => return Err(format!("parse SGX type: {err}")),
+                    }
+                }
+                ARC_PLATFORM_INSTANCE_ID => {
+                    let id = fixed_octet_string(&v)
+                        .map_err(|err| format!("parse platform instance ID: {err}"))?;
+                    out.platform_instance_id = Some(id);
+                }
+                ARC_CONFIGURATION => {
+                    let c = PlatformConfiguration::from_tlv(&v)
+                        .map_err(|err| format!("parse configuration: {err}"))?;
+                    out.configuration = Some(c);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl Display for SgxType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let v = match self {
+            Self::Standard => "Standard",
+            Self::Scalable => "Scalable",
+            Self::ScalableWithIntegrity => "ScalableWithIntegrity",
+        };
+
+        write!(f, "{v}")
+    }
+}
+
+fn fixed_octet_string<const N: usize>(v: &Tlv) -> Result<[u8; N], String> {
+    let b = v.as_octet_string()?;
+    b.try_into()
+        .map_err(|_| format!("bad length: expect {N}, got {}", b.len()))
+}
+
+/// Splits a `SEQUENCE OF SEQUENCE { OID, value }` whose OIDs are all direct children of the SGX
+/// extensions OID appended with `parent_arcs`. Each item is returned as the last arc of its OID
+/// along with the value.
+fn sgx_extension_items<'a>(
+    seq: &Tlv<'a>,
+    parent_arcs: &[u8],
+) -> Result<Vec<(u8, Tlv<'a>)>, String> {
+    if seq.tag != der::TAG_SEQUENCE {
+        return Err(format!("bad tag: expect SEQUENCE, got {:#04x}", seq.tag));
+    }
+
+    let mut prefix = OID_SGX_EXTENSIONS.to_vec();
+    prefix.extend_from_slice(parent_arcs);
+
+    let mut out = vec![];
+    for item in seq.children()? {
+        let fields = item.children()?;
+        let (id, value) = match fields.as_slice() {
+            [id, value] if id.tag == der::TAG_OID => (id, value),
+            _ => return Err("bad item: expect SEQUENCE { OID, value }".to_string()),
+        };
+
+        match id.value.strip_prefix(prefix.as_slice()) {
+            Some([arc]) => out.push((*arc, *value)),
+            _ => {
+                let hint = format!("unexpected OID: {}", hex::encode_to_string(id.value));
+                return Err(hint);
+            }
+        }
+    }
+
+    Ok(out)
+}
@@ -0,0 +1,25 @@
+//! Helpers to serialize byte arrays as hex strings, which are referred by `serialize_with`.
+
+use encoding::hex;
+use serde::ser::SerializeSeq;
+use serde::Serializer;
+
+pub(super) fn hex<S, T>(v: &T, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: AsRef<[u8]>,
+{
+    s.serialize_str(&hex::encode_to_string(v.as_ref()))
+}
+
+pub(super) fn hex_list<S, T, const N: usize>(v: &[T; N], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: AsRef<[u8]>,
+{
+    let mut seq = s.serialize_seq(Some(N))?;
+    for v in v.iter() {
+        seq.serialize_element(&hex::encode_to_string(v.as_ref()))?;
+    }
+    seq.end()
+}
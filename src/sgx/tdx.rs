@@ -0,0 +1,184 @@
+use std::fmt::Display;
+
+use encoding::hex;
+use serde::Serialize;
+
+use super::{fixed_length_pad, ser, validate_minimum_length};
+
+pub(super) const LENGTH_TD_REPORT_BODY: usize = 584;
+pub(super) const LENGTH_TD_REPORT_BODY_15: usize = 648;
+
+/// TD report body of TDX 1.0, i.e. `sgx_report2_body_t`.
+///
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.16/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_4.h#L123
+#[derive(Clone, Copy, Debug, Serialize)]
+#[repr(C, packed)]
+pub struct TdReportBody {
+    #[serde(serialize_with = "ser::hex")]
+    pub tee_tcb_svn: [u8; 16],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_seam: [u8; 48],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_signer_seam: [u8; 48],
+    #[serde(serialize_with = "ser::hex")]
+    pub seam_attributes: [u8; 8],
+    #[serde(serialize_with = "ser::hex")]
+    pub td_attributes: [u8; 8],
+    #[serde(serialize_with = "ser::hex")]
+    pub xfam: [u8; 8],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_td: [u8; 48],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_config_id: [u8; 48],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_owner: [u8; 48],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_owner_config: [u8; 48],
+    #[serde(serialize_with = "ser::hex_list")]
+    pub rt_mr: [[u8; 48]; 4],
+    #[serde(serialize_with = "ser::hex")]
+    pub report_data: [u8; 64],
+}
+const _TD_REPORT_BODY: [u8; LENGTH_TD_REPORT_BODY] = [0u8; std::mem::size_of::<TdReportBody>()];
+
+/// TD report body of TDX 1.5, i.e. `sgx_report2_body_v1_5_t`, which extends that of TDX 1.0.
+///
+/// ref: https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/DCAP_1.19/QuoteGeneration/quote_wrapper/common/inc/sgx_quote_5.h#L60
+#[derive(Clone, Copy, Debug, Serialize)]
+#[repr(C, packed)]
+pub struct TdReportBody15 {
+    #[serde(flatten)]
+    pub base: TdReportBody,
+    #[serde(serialize_with = "ser::hex")]
+    pub tee_tcb_svn2: [u8; 16],
+    #[serde(serialize_with = "ser::hex")]
+    pub mr_servicetd: [u8; 48],
+}
+const _TD_REPORT_BODY_15: [u8; LENGTH_TD_REPORT_BODY_15] =
+    [0u8; std::mem::size_of::<TdReportBody15>()];
+
+impl AsRef<[u8]> for TdReportBody {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_TD_REPORT_BODY)
+        }
+    }
+}
+
+impl Default for TdReportBody {
+    fn default() -> Self {
+        Self {
+            tee_tcb_svn: Default::default(),
+            mr_seam: [0u8; 48],
+            mr_signer_seam: [0u8; 48],
+            seam_attributes: Default::default(),
+            td_attributes: Default::default(),
+            xfam: Default::default(),
+            mr_td: [0u8; 48],
+            mr_config_id: [0u8; 48],
+            mr_owner: [0u8; 48],
+            mr_owner_config: [0u8; 48],
+            rt_mr: [[0u8; 48]; 4],
+            report_data: [0u8; 64],
+        }
+    }
+}
+
+impl Display for TdReportBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 15) };
+
+        let mut fields: Vec<(String, &[u8])> = vec![
+            ("tee_tcb_svn".to_string(), self.tee_tcb_svn.as_ref()),
+            ("mr_seam".to_string(), self.mr_seam.as_ref()),
+            ("mr_signer_seam".to_string(), self.mr_signer_seam.as_ref()),
+            ("seam_attributes".to_string(), self.seam_attributes.as_ref()),
+            ("td_attributes".to_string(), self.td_attributes.as_ref()),
+            ("xfam".to_string(), self.xfam.as_ref()),
+            ("mr_td".to_string(), self.mr_td.as_ref()),
+            ("mr_config_id".to_string(), self.mr_config_id.as_ref()),
+            ("mr_owner".to_string(), self.mr_owner.as_ref()),
+            ("mr_owner_config".to_string(), self.mr_owner_config.as_ref()),
+        ];
+        for (i, v) in self.rt_mr.iter().enumerate() {
+            fields.push((format!("rt_mr{i}"), v.as_ref()));
+        }
+        fields.push(("report_data".to_string(), self.report_data.as_ref()));
+
+        for (i, (name, v)) in fields.iter().enumerate() {
+            if i != 0 {
+                writeln!(f).expect("write line break");
+            }
+            write!(f, "{} = {}", pad(name), hex::encode_to_string(v)).expect("write field");
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for TdReportBody {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_TD_REPORT_BODY)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let out = unsafe { *(value.as_ptr() as *const Self) };
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for TdReportBody15 {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(self as *const Self as *const u8, LENGTH_TD_REPORT_BODY_15)
+        }
+    }
+}
+
+impl Default for TdReportBody15 {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            tee_tcb_svn2: Default::default(),
+            mr_servicetd: [0u8; 48],
+        }
+    }
+}
+
+impl Display for TdReportBody15 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pad = |s: &str| -> String { fixed_length_pad(s, 15) };
+
+        let base = self.base;
+        writeln!(f, "{base}").expect("write base");
+        writeln!(
+            f,
+            "{} = {}",
+            pad("tee_tcb_svn2"),
+            hex::encode_to_string(self.tee_tcb_svn2.as_ref())
+        )
+        .expect("write tee_tcb_svn2");
+        write!(
+            f,
+            "{} = {}",
+            pad("mr_servicetd"),
+            hex::encode_to_string(self.mr_servicetd.as_ref())
+        )
+        .expect("write mr_servicetd");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for TdReportBody15 {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        validate_minimum_length(value.len(), LENGTH_TD_REPORT_BODY_15)
+            .map_err(|err| format!("validate length: {err}"))?;
+
+        let out = unsafe { *(value.as_ptr() as *const Self) };
+        Ok(out)
+    }
+}